@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, KeyCode, *};
+use serde::Deserialize;
+
+use std::default::Default;
+
+// Systems ask the ActionState whether an action is held instead of checking for particular keys, so the keys can be
+// changed without touching the systems. The bindings for this example are loaded from assets/controls.actions, and
+// Control-R rebinds painting to whatever key or mouse button is pressed next.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+    Paint,
+    Rebind,
+}
+
+#[derive(Component)]
+struct Player;
+
+#[derive(Component)]
+struct Status;
+
+#[derive(Default, Resource)]
+struct Rebinding(bool);
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Actions example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(50),
+        ))
+        .init_resource::<Rebinding>()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        // The action plugin has to come after the crossterm plugin
+        .add_plugin(ActionPlugin::<Move>::default())
+        .add_startup_system(startup_system)
+        .add_system(move_player)
+        .add_system(rebind)
+        .add_system(show_status.after(rebind))
+        .run();
+}
+
+fn startup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut cursor: ResMut<Cursor>,
+    mut input_map: ResMut<InputMap<Move>>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    cursor.hidden = true;
+
+    // The bindings replace any that are already there once they're loaded, and again if the file changes
+    input_map.source = Some(asset_server.load("controls.actions"));
+
+    commands.spawn((TextBundle::default(), Status));
+    commands.spawn((
+        SpriteBundle {
+            sprite: sprites.add(Sprite::new("@")),
+            stylemap: stylemaps.add(StyleMap::default()),
+            position: Position::new(10, 5, 1),
+            ..Default::default()
+        },
+        Player,
+    ));
+}
+
+fn move_player(
+    actions: Res<ActionState<Move>>,
+    mut commands: Commands,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+    mut player: Query<&mut Position, With<Player>>,
+) {
+    let mut position = player.single_mut();
+    let (x, y) = (position.x, position.y);
+
+    // Holding a direction keeps moving, for as long as the terminal says the key is held
+    if actions.pressed(Move::Up) {
+        position.y = (position.y - 1).max(2);
+    }
+    if actions.pressed(Move::Down) {
+        position.y += 1;
+    }
+    if actions.pressed(Move::Left) {
+        position.x = (position.x - 1).max(0);
+    }
+    if actions.pressed(Move::Right) {
+        position.x += 1;
+    }
+
+    // Painting leaves a trail behind the player
+    if actions.pressed(Move::Paint) {
+        commands.spawn(SpriteBundle {
+            sprite: sprites.add(Sprite::new("#")),
+            stylemap: stylemaps.add(StyleMap::with_fg(Color::Green)),
+            position: Position::new(x, y, 0),
+            ..Default::default()
+        });
+    }
+}
+
+fn rebind(
+    actions: Res<ActionState<Move>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut keys: EventReader<KeyEvent>,
+    mut mouse: EventReader<MouseEvent>,
+    mut input_map: ResMut<InputMap<Move>>,
+) {
+    // Whatever is pressed once rebinding has started becomes the only binding for painting. The events are read
+    // either way, so the Control-R that starts it isn't picked up
+    let pressed_key = keys.iter().filter_map(Binding::from_key_event).last();
+    let pressed_button = mouse.iter().filter_map(Binding::from_mouse_event).last();
+    let binding = pressed_key.or(pressed_button);
+    if !rebinding.0 {
+        if actions.just_pressed(Move::Rebind) {
+            rebinding.0 = true;
+        }
+        return;
+    }
+
+    if let Some(binding) = binding {
+        input_map.rebind(Move::Paint, binding);
+        rebinding.0 = false;
+    }
+}
+
+fn show_status(
+    input_map: Res<InputMap<Move>>,
+    rebinding: Res<Rebinding>,
+    mut status: Query<&mut Text, With<Status>>,
+) {
+    if !input_map.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+
+    let message = if rebinding.0 {
+        "Press the key or mouse button to paint with".to_string()
+    } else {
+        let paint: Vec<String> = input_map.bindings(Move::Paint).iter().map(describe).collect();
+        format!(
+            "WASD or the arrow keys move, {} paints. Control-R rebinds painting, Control-C quits",
+            paint.join(" or ")
+        )
+    };
+    *status.single_mut() = Text::from_section(message, Style::default());
+}
+
+// A short name for a binding, like "CONTROL+Left click"
+fn describe(binding: &Binding) -> String {
+    let (name, modifiers) = match binding {
+        Binding::Key {
+            code: KeyCode::Char(' '),
+            modifiers,
+        } => ("space".to_string(), modifiers),
+        Binding::Key {
+            code: KeyCode::Char(c),
+            modifiers,
+        } => (c.to_string(), modifiers),
+        Binding::Key { code, modifiers } => (format!("{:?}", code), modifiers),
+        Binding::Mouse { button, modifiers } => (format!("{:?} click", button), modifiers),
+    };
+    if modifiers.is_empty() {
+        name
+    } else {
+        format!("{:?}+{}", modifiers, name)
+    }
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{KeyCode, *};
+
+use std::default::Default;
+use std::time::Duration;
+
+// An AnimationPlayer shows the frames of a SpriteAnimation one after another by swapping out the entity's sprite. This
+// example loads a ping-pong animation from assets/face.anim, where the first and last frames also set a stylemap, and
+// builds a looping spinner in code. Press space to pause and unpause the face.
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Animation example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(toggle_pause)
+        .run();
+}
+
+#[derive(Component)]
+struct Face;
+
+fn startup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+    mut animations: ResMut<Assets<SpriteAnimation>>,
+) {
+    cursor.hidden = true;
+    let plain = stylemaps.add(StyleMap::default());
+
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("Press space to pause the face, and Control-C to quit")),
+        stylemap: plain.clone(),
+        ..Default::default()
+    });
+
+    // The sprite handle is replaced by the first frame as soon as the animation has loaded
+    commands.spawn((
+        SpriteBundle {
+            position: Position::with_xy(4, 3),
+            stylemap: plain.clone(),
+            ..Default::default()
+        },
+        AnimationPlayer::new(asset_server.load("face.anim")),
+        Face,
+    ));
+
+    let frames = ["|", "/", "-", "\\"]
+        .iter()
+        .map(|frame| Frame::new(sprites.add(Sprite::new(frame)), Duration::from_millis(100)))
+        .collect();
+    let spinner = animations.add(SpriteAnimation::new(frames, AnimationMode::Loop));
+
+    commands.spawn((
+        SpriteBundle {
+            position: Position::with_xy(16, 4),
+            stylemap: plain,
+            ..Default::default()
+        },
+        AnimationPlayer::new(spinner),
+    ));
+}
+
+fn toggle_pause(mut keys: EventReader<KeyEvent>, mut faces: Query<&mut AnimationPlayer, With<Face>>) {
+    let pressed = keys.iter().any(|key| {
+        key.code == KeyCode::Char(' ') && key.kind != crossterm::event::KeyEventKind::Release
+    });
+    if pressed {
+        for mut player in faces.iter_mut() {
+            player.paused = !player.paused;
+        }
+    }
+}
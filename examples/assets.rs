@@ -45,7 +45,7 @@ pub fn main() {
         .run();
 }
 
-static ASSETS: &[&str] = &["demo/title.txt", "demo/title.stylemap"];
+static ASSETS: &[&str] = &["demo/title.txt", "demo/title.legend.stylemap"];
 
 #[derive(Resource, Deref, DerefMut)]
 struct UntypedHandles(Vec<HandleUntyped>);
@@ -92,7 +92,6 @@ fn create_entities(
     mut windows: Query<&mut CrosstermWindow, With<PrimaryWindow>>,
     asset_server: Res<AssetServer>,
     sprites: Res<Assets<Sprite>>,
-    stylemaps: Res<Assets<StyleMap>>
 ) {
     let mut window = windows.single_mut();
     // I want to center the title, so i needed to wait until it was loaded before I could actually access
@@ -106,7 +105,7 @@ fn create_entities(
     commands.spawn(SpriteBundle {
         sprite: title_handle.clone(),
         position: Position::with_xy(center_x, center_y),
-        stylemap: asset_server.get_handle("demo/title.stylemap"),
+        stylemap: asset_server.get_handle("demo/title.legend.stylemap"),
         ..Default::default()
     });
 }
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, *};
+
+use std::default::Default;
+
+// A Canvas has several pixels to a cell, so it can plot things in more detail than a sprite. This example draws a
+// moving sine wave with braille characters, inside a panel, along with the same wave in the coarser quadrant and half
+// block modes underneath.
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Canvas example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(plot)
+        .run();
+}
+
+fn startup_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    cursor.hidden = true;
+
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("Press Control-C to quit")),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+
+    commands.spawn(PanelBundle {
+        panel: Panel::new(42, 12).with_border(Border::Rounded).with_title("Braille"),
+        position: Position::with_xy(2, 2),
+        ..Default::default()
+    });
+
+    let modes = [
+        (CanvasMode::Braille, Position::new(3, 3, 1)),
+        (CanvasMode::Quadrant, Position::with_xy(2, 15)),
+        (CanvasMode::HalfBlock, Position::with_xy(2, 26)),
+    ];
+    for (mode, position) in modes {
+        commands.spawn(CanvasBundle {
+            canvas: Canvas::with_mode(40, 10, mode),
+            position,
+            ..Default::default()
+        });
+    }
+}
+
+fn plot(time: Res<Time>, mut canvases: Query<&mut Canvas>) {
+    let phase = time.elapsed_seconds();
+    for mut canvas in canvases.iter_mut() {
+        canvas.clear();
+
+        // The axis is drawn in grey, and the wave changes color as it goes along
+        let (width, height) = (canvas.width() as i32, canvas.height() as i32);
+        canvas.pen = Some(Color::DarkGrey);
+        canvas.line((0, height / 2), (width - 1, height / 2));
+
+        let amplitude = (height as f32 - 1.0) / 2.0;
+        let wave = |x: i32| (amplitude - amplitude * (x as f32 / 8.0 + phase * 2.0).sin()).round() as i32;
+        let mut last = (0, wave(0));
+        for x in 1..width {
+            canvas.pen = Some(match x * 3 / width {
+                0 => Color::Cyan,
+                1 => Color::Green,
+                _ => Color::Yellow,
+            });
+            let point = (x, wave(x));
+            canvas.line(last, point);
+            last = point;
+        }
+    }
+}
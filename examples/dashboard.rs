@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{KeyCode, *};
+
+use std::default::Default;
+
+// An app that only updates when something happens, so it sits at no CPU at all while idle. This one updates when a
+// key is pressed, and at least once a second to keep its clock ticking. Pressing space plays a short countdown, which
+// asks for the updates it needs with RequestRedraw.
+
+#[derive(Default, Resource)]
+struct Stats {
+    updates: usize,
+    keys: usize,
+    countdown: Option<Timer>,
+}
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Dashboard example").set_update_mode(UpdateMode::OnDemand {
+        max_wait: Some(std::time::Duration::from_secs(1)),
+    });
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .init_resource::<Stats>()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(update_stats)
+        .add_system(show_stats.after(update_stats))
+        .run();
+}
+
+fn startup_system(mut commands: Commands, mut cursor: ResMut<Cursor>) {
+    cursor.hidden = true;
+    commands.spawn(TextBundle::default());
+}
+
+fn update_stats(
+    time: Res<Time>,
+    mut stats: ResMut<Stats>,
+    mut keys: EventReader<KeyEvent>,
+    mut redraw: EventWriter<bevy::window::RequestRedraw>,
+) {
+    stats.updates += 1;
+    for key in keys.iter().filter(|key| key.kind != crossterm::event::KeyEventKind::Release) {
+        stats.keys += 1;
+        if key.code == KeyCode::Char(' ') {
+            stats.countdown = Some(Timer::from_seconds(3.0, TimerMode::Once));
+        }
+    }
+
+    // While the countdown runs it needs updating every frame, not just once a second
+    if let Some(countdown) = &mut stats.countdown {
+        countdown.tick(time.delta());
+        if countdown.finished() {
+            stats.countdown = None;
+        } else {
+            redraw.send(bevy::window::RequestRedraw);
+        }
+    }
+}
+
+fn show_stats(time: Res<Time>, stats: Res<Stats>, mut text: Query<&mut Text>) {
+    let countdown = match &stats.countdown {
+        Some(countdown) => format!("{:.2}", countdown.remaining_secs()),
+        None => "press space to start".to_string(),
+    };
+    let report = format!(
+        "Running for {}s\nUpdates: {}\nKey events: {}\nCountdown: {}\n\nPress Control-C to quit",
+        time.elapsed().as_secs(),
+        stats.updates,
+        stats.keys,
+        countdown,
+    );
+    *text.single_mut() = Text::from_section(report, Style::default());
+}
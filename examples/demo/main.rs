@@ -1,5 +1,5 @@
-use bevy::{prelude::*, log::{LogPlugin, self}};
-use bevy_crossterm::prelude::*;
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{KeyCode, *};
 
 use std::default::Default;
 
@@ -102,7 +102,10 @@ fn loading_system(
     cursor.hidden = true;
 
     // Load the assets we want
-    let handles = asset_server.load_folder("demo").unwrap();
+    let mut handles = asset_server.load_folder("demo").unwrap();
+    // Only each file's main asset is kept hold of by load_folder, so the ball's stylemap would be freed as soon as it
+    // loaded without a handle of its own
+    handles.push(asset_server.load_untyped("demo/bounce.tsprite#stylemap"));
 
     commands.insert_resource(HandleCollection(handles));
 }
@@ -126,11 +129,8 @@ fn check_for_loaded(
            }).collect();
 
     let data = asset_server.get_group_load_state(handles.iter().map(|handle| handle.id()));
-    match data {
-        bevy::asset::LoadState::Loaded => {
-            next_state.set(GameState::Title);
-        },
-        _ => {}
+    if data == bevy::asset::LoadState::Loaded {
+        next_state.set(GameState::Title);
     }
 }
 
@@ -141,9 +141,9 @@ pub fn demo_setup(mut commands: Commands) {
     commands.insert_resource(SceneRoot(scene_root));
 }
 
-// Helper function to see if there was a key press this frame
-pub fn detect_keypress(mut keys: EventReader<KeyEvent>) -> bool {
-    keys.iter().last().is_some()
+// Helper function to see if a key went down this frame. Keys being held or let go of don't count
+pub fn detect_keypress(keys: &Input<KeyCode>) -> bool {
+    keys.get_just_pressed().next().is_some()
 }
 
 // Simple update function that most screens will use
@@ -151,9 +151,9 @@ pub fn just_wait_and_advance(
     state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>,>,
     mut app_exit: ResMut<Events<bevy::app::AppExit>>,
-    keys: EventReader<KeyEvent>,
+    keys: Res<Input<KeyCode>>,
 ) {
-    if detect_keypress(keys) {
+    if detect_keypress(&keys) {
         if let Some(next_stage) = state.0.next_state() {
             next_state.set(next_stage);
         } else {
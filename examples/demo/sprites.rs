@@ -9,36 +9,39 @@ pub fn setup(
     scene_root: Res<SceneRoot>,
     window: Query<&CrosstermWindow, With<PrimaryWindow>>,
     asset_server: Res<AssetServer>,
-    mut sprites: ResMut<Assets<Sprite>>,
+    sprites: Res<Assets<Sprite>>,
     mut stylemaps: ResMut<Assets<StyleMap>>,
 ) {
     const MARGIN: i32 = 4;
 
     let window = window.single();
 
-    let mut explain_text = "Sprites are the lifeblood of bevy_crossterm.\n\nSprites are rectangular regions of (unicode) text which are rendered on the screen at a given Position. Sprites can also be visible/invisible and \"transparent\" which allows bevy_crossterm to skip rendering unstyled spaces, so the sprites underneath are visible.\n\nPositions have an x, y, and z coordinate to allow them to be properly ordered front-to-back.".to_string();
     let explain_width = window.x_center() as i32 - MARGIN * 2; // Half the screen plus some margins
-    textwrap::fill_inplace(&mut explain_text, explain_width as usize);
-    let explain_text = explain_text.replace("\n", "\n\n");
-    let explain_sprite = Sprite::new(explain_text);
+    let bold = Style::with_attrib(Attribute::Bold);
+    let explain_text = Text::from_sections(vec![
+        TextSection::new("Sprites", bold),
+        TextSection::new(" are the lifeblood of bevy_crossterm.\n\nSprites are rectangular regions of (unicode) text which are rendered on the screen at a given ", Style::default()),
+        TextSection::new("Position", bold),
+        TextSection::new(". Sprites can also be visible/invisible and \"transparent\" which allows bevy_crossterm to skip rendering unstyled spaces, so the sprites underneath are visible.\n\nPositions have an x, y, and z coordinate to allow them to be properly ordered front-to-back.", Style::default()),
+    ])
+    .with_max_width(explain_width as usize);
+    let (explain_sprite, _) = explain_text.layout();
     let explain_pos = Position::with_xy(
         MARGIN,
         window.y_center() as i32 - explain_sprite.y_center() as i32,
     );
 
-    let default_style = stylemaps.add(StyleMap::default());
-
     let big_box_handle: Handle<Sprite> = asset_server.get_handle("demo/big_box.txt");
     let big_box_sprite = sprites.get(&big_box_handle).unwrap();
     let big_box_pos = Position::with_xy(
         window.width() as i32 / 4 * 3 - big_box_sprite.width() as i32 - MARGIN,
-        window.height() as i32 / 10 * 1,
+        window.height() as i32 / 10,
     );
 
     let small_box_handle: Handle<Sprite> = asset_server.get_handle("demo/small_box.txt");
     let small_box_pos = Position::with_xy(
         window.width() as i32 / 4 * 3 + MARGIN,
-        window.height() as i32 / 10 * 1 + 1,
+        window.height() as i32 / 10 + 1,
     );
 
     let big_combo_pos = Position::with_xy(
@@ -68,9 +71,8 @@ pub fn setup(
     let transparent = Visible::transparent();
 
     commands.spawn((
-        SpriteBundle {
-            sprite: sprites.add(explain_sprite),
-            stylemap: default_style.clone(),
+        TextBundle {
+            text: explain_text,
             position: explain_pos,
             ..Default::default()
         },
@@ -124,6 +126,7 @@ pub fn setup(
             position: small_combo_trans_pos,
             stylemap: white_handle.clone(),
             visible: transparent,
+            ..Default::default()
         },
     )).set_parent(scene_root.0);
 }
@@ -17,7 +17,7 @@ pub fn setup(
     let title_sprite = sprites.get(&title_handle).unwrap();
     let title_pos = Position::with_xy(
         window.x_center() as i32 - title_sprite.x_center() as i32,
-        window.height() as i32 / 10 * 1,
+        window.height() as i32 / 10,
     );
 
     let welcome_sprite = Sprite::new("Welcome to the bevy_crossterm demo!");
@@ -44,7 +44,7 @@ pub fn setup(
         SpriteBundle {
             sprite: title_handle,
             position: title_pos,
-            stylemap: asset_server.load("demo/title.stylemap"),
+            stylemap: asset_server.load("demo/title.legend.stylemap"),
             ..default()
         },
     ).set_parent(scene_root.0);
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, *};
+
+// Headless mode renders into a virtual screen instead of the terminal, which makes it possible to run bevy_crossterm
+// apps somewhere without a tty (like CI) and check what was actually drawn. This example steps an app by hand and
+// checks the same transparency and redraw cases the transparency and redraw_demo examples show off.
+
+#[derive(Component)]
+struct Tag;
+
+pub fn main() {
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_headless(20, 5);
+
+    let mut app = App::new();
+    app.insert_resource(settings)
+        // No DefaultPlugins here, there's no need for a window or input handling
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system);
+
+    // Run the startup systems and draw the first frame
+    app.update();
+
+    let screen = app.world.resource::<HeadlessBackend>().clone();
+    print_screen(&screen);
+
+    // The "@" sits on top of the box, and the transparent ring lets the box show through its hole
+    assert_eq!(screen.line(1).unwrap(), "  #####             ");
+    assert_eq!(screen.line(2).unwrap(), "  #@  #             ");
+    assert_eq!(screen.cell(4, 2).unwrap().background, Color::White);
+    assert_eq!(screen.cell(3, 2).unwrap().background, Color::Reset);
+
+    // Move the ring off of the box. The box has to be repaired where the ring used to be
+    for mut pos in app.world.query_filtered::<&mut Position, With<Tag>>().iter_mut(&mut app.world) {
+        pos.x += 10;
+    }
+    app.update();
+    print_screen(&screen);
+
+    assert_eq!(screen.line(1).unwrap(), "            #####   ");
+    assert_eq!(screen.line(2).unwrap(), "   @        #   #   ");
+    assert_eq!(screen.cell(2, 2).unwrap().background, Color::White);
+    assert_eq!(screen.cell(3, 2).unwrap().background, Color::Reset);
+
+    println!("Everything was drawn where it should be");
+}
+
+fn startup_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    cursor.hidden = true;
+
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("     \n     \n     ")),
+        position: Position::new(2, 1, 0),
+        stylemap: stylemaps.add(StyleMap::with_bg(Color::White)),
+        ..Default::default()
+    });
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("@")),
+        position: Position::new(3, 2, 1),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+    commands
+        .spawn(SpriteBundle {
+            sprite: sprites.add(Sprite::new("#####\n#   #\n#####")),
+            position: Position::new(2, 1, 2),
+            stylemap: stylemaps.add(StyleMap::default()),
+            visible: Visible::transparent(),
+            ..Default::default()
+        })
+        .insert(Tag);
+}
+
+fn print_screen(screen: &HeadlessBackend) {
+    println!("+{}+", "-".repeat(screen.width() as usize));
+    for line in screen.lines() {
+        println!("|{}|", line);
+    }
+    println!("+{}+", "-".repeat(screen.width() as usize));
+}
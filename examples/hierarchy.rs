@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_crossterm::prelude::*;
+
+use std::default::Default;
+
+// Entities with a parent are positioned relative to it. This example builds a ship out of a hull and a turret that's
+// a child of the hull, so moving the hull moves the whole ship. The turret also spins on its own, relative to the hull.
+
+#[derive(Component)]
+struct Hull {
+    direction: i32,
+}
+
+#[derive(Component)]
+struct Turret;
+
+#[derive(Resource)]
+struct Interval(Timer);
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Hierarchy example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .insert_resource(Interval(Timer::from_seconds(0.1, TimerMode::Repeating)))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(move_ship)
+        .run();
+}
+
+static HULL: &str = "  ___\n<[___]>";
+
+fn startup_system(
+    mut commands: Commands,
+    window: Query<&CrosstermWindow, With<PrimaryWindow>>,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    let window = window.single();
+    cursor.hidden = true;
+
+    let plain = stylemaps.add(StyleMap::default());
+
+    commands
+        .spawn(SpriteBundle {
+            sprite: sprites.add(Sprite::new(HULL)),
+            position: Position::with_xy(0, window.y_center() as i32),
+            stylemap: plain.clone(),
+            ..Default::default()
+        })
+        .insert(Hull { direction: 1 })
+        .with_children(|hull| {
+            // The turret sits on top of the hull, and is drawn over it
+            hull.spawn(SpriteBundle {
+                sprite: sprites.add(Sprite::new("|")),
+                position: Position::new(3, -1, 1),
+                stylemap: plain,
+                ..Default::default()
+            })
+            .insert(Turret);
+        });
+
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("Press Control-C to quit")),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+}
+
+fn move_ship(
+    time: Res<Time>,
+    window: Query<&CrosstermWindow, With<PrimaryWindow>>,
+    mut timer: ResMut<Interval>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut hulls: Query<(&mut Hull, &mut Position)>,
+    turrets: Query<&Handle<Sprite>, With<Turret>>,
+) {
+    let window = window.single();
+    timer.0.tick(time.delta());
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    // Only the hull moves, the turret follows along because it's a child
+    for (mut hull, mut pos) in hulls.iter_mut() {
+        pos.x += hull.direction;
+        if pos.x <= 0 || pos.x + 7 >= window.width() as i32 {
+            hull.direction = -hull.direction;
+        }
+    }
+
+    // Spinning is done by changing the turret's sprite
+    for turret in turrets.iter() {
+        if let Some(sprite) = sprites.get_mut(turret) {
+            let next = match sprite.data() {
+                "|" => "/",
+                "/" => "-",
+                "-" => "\\",
+                _ => "|",
+            };
+            sprite.update(next);
+        }
+    }
+}
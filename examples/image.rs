@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::*;
+
+use std::default::Default;
+
+// Images load straight into a sprite and a stylemap, drawn with half blocks so every cell shows two pixels. The
+// see-through corners of assets/disc.png let the text underneath show through. assets/disc_small.timage loads the same
+// image smaller and with only 16 colors, dithered to make up for it.
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Image example");
+    // Plain images aren't loaded as sprites unless asked for
+    settings.set_load_images(true);
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .run();
+}
+
+fn startup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    cursor.hidden = true;
+
+    // Something for the corners of the disc to show
+    let background = format!("{}\n", "~ ".repeat(20)).repeat(12);
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new(format!("Press Control-C to quit\n{}", background))),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+
+    commands.spawn(SpriteBundle {
+        sprite: asset_server.load("disc.png"),
+        stylemap: asset_server.load("disc.png#stylemap"),
+        position: Position::new(2, 2, 1),
+        ..Default::default()
+    });
+
+    commands.spawn(SpriteBundle {
+        sprite: asset_server.load("disc_small.timage"),
+        stylemap: asset_server.load("disc_small.timage#stylemap"),
+        position: Position::new(30, 2, 1),
+        ..Default::default()
+    });
+}
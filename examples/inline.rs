@@ -0,0 +1,94 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, *};
+
+use std::default::Default;
+
+// Command line tools usually shouldn't take over the whole terminal. In inline mode the app draws into a few rows just
+// below the shell prompt, and whatever it drew last stays in the scrollback once it exits. This example is a progress
+// bar that fills up and then exits by itself.
+
+const BAR_WIDTH: usize = 40;
+
+#[derive(Resource)]
+struct Progress {
+    timer: Timer,
+    done: usize,
+}
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_inline(2);
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .insert_resource(Progress {
+            timer: Timer::from_seconds(0.05, TimerMode::Repeating),
+            done: 0,
+        })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(update_progress)
+        .run();
+}
+
+#[derive(Component)]
+struct Bar;
+
+fn startup_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    cursor.hidden = true;
+
+    // Positions are relative to the viewport, so the top row of the viewport is y = 0
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("Downloading...")),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+
+    commands
+        .spawn(SpriteBundle {
+            sprite: sprites.add(Sprite::new(format!("[{}]", " ".repeat(BAR_WIDTH)))),
+            position: Position::with_y(1),
+            stylemap: stylemaps.add(StyleMap::with_fg(Color::Green)),
+            ..Default::default()
+        })
+        .insert(Bar);
+}
+
+fn update_progress(
+    time: Res<Time>,
+    mut progress: ResMut<Progress>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    bar: Query<&Handle<Sprite>, With<Bar>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    progress.timer.tick(time.delta());
+    if !progress.timer.just_finished() {
+        return;
+    }
+
+    if progress.done == BAR_WIDTH {
+        app_exit.send(AppExit);
+        return;
+    }
+    progress.done += 1;
+
+    if let Some(sprite) = sprites.get_mut(bar.single()) {
+        sprite.update(format!(
+            "[{}{}]",
+            "#".repeat(progress.done),
+            " ".repeat(BAR_WIDTH - progress.done)
+        ));
+    }
+}
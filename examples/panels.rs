@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, KeyCode, *};
+
+use std::default::Default;
+
+// A Panel is a box with a border that's drawn by the plugin, so it can be resized by changing the component. Use the
+// arrow keys to resize the panel, and tab to switch between the border presets and a nine-slice built from a sprite.
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Panels example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(resize_panel)
+        .run();
+}
+
+#[derive(Resource)]
+struct Borders(Vec<Border>);
+
+fn startup_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    cursor.hidden = true;
+
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new(
+            "Arrow keys resize the panel, tab changes its border, and Control-C quits",
+        )),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+
+    let blocks = sprites.add(Sprite::new("▛▀▜\n▌ ▐\n▙▄▟"));
+    commands.insert_resource(Borders(vec![
+        Border::Single,
+        Border::Double,
+        Border::Rounded,
+        Border::Heavy,
+        Border::Ascii,
+        Border::NineSlice(NineSlice::new(blocks)),
+    ]));
+
+    commands.spawn(PanelBundle {
+        panel: Panel::new(30, 10)
+            .with_style(Style::with_fg(Color::Cyan))
+            .with_title("Single")
+            .with_title_style(Style::with_attrib(Attribute::Bold)),
+        position: Position::with_xy(4, 2),
+        ..Default::default()
+    });
+}
+
+fn resize_panel(mut keys: EventReader<KeyEvent>, borders: Res<Borders>, mut panels: Query<&mut Panel>) {
+    for key in keys.iter() {
+        if key.kind == crossterm::event::KeyEventKind::Release {
+            continue;
+        }
+
+        for mut panel in panels.iter_mut() {
+            match key.code {
+                KeyCode::Left => panel.width = panel.width.saturating_sub(1),
+                KeyCode::Right => panel.width += 1,
+                KeyCode::Up => panel.height = panel.height.saturating_sub(1),
+                KeyCode::Down => panel.height += 1,
+                KeyCode::Tab => {
+                    let current = borders.0.iter().position(|border| *border == panel.border);
+                    let next = current.map_or(0, |index| (index + 1) % borders.0.len());
+                    panel.border = borders.0[next].clone();
+                    let title = match &panel.border {
+                        Border::NineSlice(_) => "Nine-slice".to_string(),
+                        border => format!("{:?}", border),
+                    };
+                    panel.title = Some(title);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use bevy_crossterm::prelude::{Color, KeyCode, *};
+
+use std::default::Default;
+
+// Pasted text arrives all at once as a PasteEvent, instead of one key event per character. This example keeps a line
+// of text that can be typed into or pasted into, and dims it while the terminal doesn't have focus. Once the terminal
+// has been torn down the text is printed to the normal screen, and escape exits with a failing exit code.
+
+#[derive(Resource)]
+struct Entry {
+    text: String,
+    focused: bool,
+}
+
+#[derive(Component)]
+struct EntryLine;
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Paste example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .insert_resource(Entry {
+            text: String::new(),
+            focused: true,
+        })
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(edit)
+        .add_system(show.after(edit))
+        .add_system(print_entry.in_schedule(Teardown))
+        .run();
+
+    // The runner leaves exiting to the app, so a failing exit code has to be passed on here
+    if let Some(teardown) = last_teardown() {
+        std::process::exit(teardown.exit_code);
+    }
+}
+
+fn startup_system(mut commands: Commands, mut cursor: ResMut<Cursor>) {
+    cursor.hidden = true;
+
+    commands.spawn(TextBundle {
+        text: Text::from_section(
+            "Type or paste some text. Enter finishes, escape gives up",
+            Style::default(),
+        ),
+        ..Default::default()
+    });
+    commands.spawn((
+        TextBundle {
+            position: Position::with_xy(0, 2),
+            ..Default::default()
+        },
+        EntryLine,
+    ));
+}
+
+fn edit(
+    mut entry: ResMut<Entry>,
+    mut keys: EventReader<KeyEvent>,
+    mut pastes: EventReader<PasteEvent>,
+    mut focus: EventReader<WindowFocused>,
+    mut exit_code: ResMut<ExitCode>,
+    mut app_exit: EventWriter<bevy::app::AppExit>,
+) {
+    for key in keys.iter().filter(|key| key.kind != crossterm::event::KeyEventKind::Release) {
+        match key.code {
+            KeyCode::Char(c) => entry.text.push(c),
+            KeyCode::Backspace => {
+                entry.text.pop();
+            }
+            KeyCode::Enter => app_exit.send(bevy::app::AppExit),
+            KeyCode::Esc => {
+                exit_code.0 = 1;
+                app_exit.send(bevy::app::AppExit);
+            }
+            _ => {}
+        }
+    }
+
+    // Pasted newlines are kept on one line, since there's only the one
+    for paste in pastes.iter() {
+        entry.text.push_str(&paste.0.replace(['\r', '\n'], " "));
+    }
+
+    if let Some(event) = focus.iter().last() {
+        entry.focused = event.focused;
+    }
+}
+
+fn show(entry: Res<Entry>, mut line: Query<&mut Text, With<EntryLine>>) {
+    if !entry.is_changed() {
+        return;
+    }
+
+    let style = match entry.focused {
+        true => Style::with_fg(Color::White),
+        false => Style::with_fg(Color::DarkGrey),
+    };
+    *line.single_mut() = Text::from_section(format!("> {}", entry.text), style);
+}
+
+fn print_entry(entry: Res<Entry>, mut teardown: EventReader<TerminalTeardown>) {
+    for event in teardown.iter() {
+        match event.exit_code {
+            0 => println!("You entered: {}", entry.text),
+            _ => println!("Gave up on: {}", entry.text),
+        }
+    }
+}
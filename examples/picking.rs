@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, *};
+
+use std::default::Default;
+
+// Entities with the Pickable component get PickingEvents when the mouse interacts with them. This example has two
+// overlapping buttons that light up when hovered and count how many times they've been clicked. Only the topmost button
+// under the mouse is picked, and the hole in the middle of the ring is see-through, so clicks there reach the box below.
+
+#[derive(Component)]
+struct Button {
+    label: &'static str,
+    clicks: usize,
+}
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Picking example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(handle_picking)
+        .run();
+}
+
+fn startup_system(
+    mut commands: Commands,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("Hover over and click the shapes. Press Control-C to quit")),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: sprites.add(Sprite::new("          \n          \n          \n          \n          ")),
+            position: Position::new(4, 3, 0),
+            stylemap: stylemaps.add(StyleMap::with_bg(Color::DarkBlue)),
+            ..Default::default()
+        },
+        Button {
+            label: "Box",
+            clicks: 0,
+        },
+        Pickable,
+    ));
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: sprites.add(Sprite::new("#######\n#     #\n#     #\n#######")),
+            position: Position::new(10, 5, 1),
+            stylemap: stylemaps.add(StyleMap::default()),
+            visible: Visible::transparent(),
+            ..Default::default()
+        },
+        Button {
+            label: "Ring",
+            clicks: 0,
+        },
+        Pickable,
+    ));
+}
+
+fn handle_picking(
+    mut picking_events: EventReader<PickingEvent>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut buttons: Query<(&mut Button, &Handle<StyleMap>)>,
+    mut status: Local<Option<Handle<Sprite>>>,
+    mut commands: Commands,
+) {
+    for event in picking_events.iter() {
+        let (mut button, stylemap) = match buttons.get_mut(event.entity) {
+            Ok(button) => button,
+            Err(_) => continue,
+        };
+
+        let message = match event.kind {
+            PickingKind::HoverEnter => {
+                if let Some(stylemap) = stylemaps.get_mut(stylemap) {
+                    stylemap.style.colors.foreground = Some(Color::Yellow);
+                }
+                format!("Hovering over the {}", button.label)
+            }
+            PickingKind::HoverLeave => {
+                if let Some(stylemap) = stylemaps.get_mut(stylemap) {
+                    stylemap.style.colors.foreground = None;
+                }
+                format!("Left the {}", button.label)
+            }
+            PickingKind::Clicked(_) => {
+                button.clicks += 1;
+                format!("Clicked the {} {} times", button.label, button.clicks)
+            }
+            PickingKind::Dragged(_) => format!("Dragging from the {}", button.label),
+            _ => continue,
+        };
+
+        // Show what happened at the bottom of the instructions
+        match &*status {
+            Some(handle) => {
+                if let Some(sprite) = sprites.get_mut(handle) {
+                    sprite.update(format!("{:<40}", message));
+                }
+            }
+            None => {
+                let handle = sprites.add(Sprite::new(format!("{:<40}", message)));
+                commands.spawn(SpriteBundle {
+                    sprite: handle.clone(),
+                    position: Position::with_y(1),
+                    stylemap: stylemaps.add(StyleMap::default()),
+                    ..Default::default()
+                });
+                *status = Some(handle);
+            }
+        }
+    }
+}
@@ -8,14 +8,13 @@ use std::default::Default;
 use std::time::Duration;
 
 // This is probably the busiest example. This demonstrates that bevy_crossterm's incrememntal drawing
-// system will properly redraw sprites when required. To simulate movement - bevy_crossterm blanks out the
-// previous position/size of any sprites that changed before they're drawn again. This leave empty holes if
-// a sprite changed while over top of another sprite. This is illustrated by the "@" going on top of the big box.
-// bevy_crossterm runs a collision detection routine and sees that the box is going to be partially erased when
-// the "@" is cleared, so it adds it to the draw candidate list even though it never "changed".
+// system will properly redraw sprites when required. Every frame bevy_crossterm composites all the visible sprites
+// into a buffer of cells, from the lowest z to the highest, and compares it to what's already on the screen. Only the
+// cells that are different get drawn. This is illustrated by the "@" going on top of the big box: as it moves, the box
+// shows up again wherever the "@" used to be even though the box itself never "changed".
 
-// Furthermore, this example also illustrates that the collision detection happens recusively - if it weren't for that,
-// the "#" would be overwritten when the box is redrawn.
+// Furthermore, the "#" sits on top of the box and is never overwritten when the box is repaired around it, because
+// the whole screen is composited in z order rather than individual sprites being redrawn.
 
 #[derive(Resource)]
 struct Interval(Timer);
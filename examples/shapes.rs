@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, *};
+
+use std::default::Default;
+
+// Shapes are drawn by the plugin from a description, instead of from a sprite built by hand. This example draws a few
+// of each kind, and a line that follows the mouse around.
+
+pub fn main() {
+    // Window settings must happen before the crossterm Plugin
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_title("Shapes example");
+
+    App::new()
+        .insert_resource(settings)
+        .insert_resource(bevy::core::TaskPoolOptions::with_num_threads(1))
+        .insert_resource(bevy::app::ScheduleRunnerSettings::run_loop(
+            std::time::Duration::from_millis(16),
+        ))
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system)
+        .add_system(follow_mouse)
+        .run();
+}
+
+#[derive(Component)]
+struct Pointer;
+
+fn startup_system(
+    mut commands: Commands,
+    mut cursor: ResMut<Cursor>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    cursor.hidden = true;
+
+    commands.spawn(SpriteBundle {
+        sprite: sprites.add(Sprite::new("Move the mouse around, and press Control-C to quit")),
+        stylemap: stylemaps.add(StyleMap::default()),
+        ..Default::default()
+    });
+
+    commands.spawn(ShapeBundle {
+        shape: Shape::rect(12, 6).with_style(Style::with_fg(Color::Blue)),
+        position: Position::with_xy(2, 2),
+        ..Default::default()
+    });
+    commands.spawn(ShapeBundle {
+        shape: Shape::filled_rect(8, 3).with_grapheme("▒").with_style(Style::with_fg(Color::DarkBlue)),
+        position: Position::with_xy(4, 3),
+        ..Default::default()
+    });
+    commands.spawn(ShapeBundle {
+        shape: Shape::ellipse(16, 8).with_grapheme("o").with_style(Style::with_fg(Color::Green)),
+        position: Position::with_xy(16, 2),
+        ..Default::default()
+    });
+    commands.spawn(ShapeBundle {
+        shape: Shape::filled_ellipse(8, 4).with_style(Style::with_fg(Color::Yellow)),
+        position: Position::with_xy(20, 4),
+        ..Default::default()
+    });
+    commands.spawn(ShapeBundle {
+        shape: Shape::polygon(vec![(0, 0), (10, 5), (-8, 6)])
+            .with_grapheme("+")
+            .with_style(Style::with_fg(Color::Magenta)),
+        position: Position::with_xy(44, 2),
+        ..Default::default()
+    });
+
+    // The line starts at its entity's position, and is drawn over everything else
+    commands.spawn((
+        ShapeBundle {
+            shape: Shape::line((0, 0), (0, 0)).with_grapheme("*").with_style(Style::with_fg(Color::Red)),
+            position: Position::new(30, 12, 1),
+            ..Default::default()
+        },
+        Pointer,
+    ));
+}
+
+fn follow_mouse(mut mouse: EventReader<MouseEvent>, mut pointers: Query<(&Position, &mut Shape), With<Pointer>>) {
+    if let Some(event) = mouse.iter().last() {
+        for (position, mut shape) in pointers.iter_mut() {
+            let to = (event.column as i32 - position.x, event.row as i32 - position.y);
+            shape.kind = ShapeKind::Line { from: (0, 0), to };
+        }
+    }
+}
@@ -63,5 +63,6 @@ fn startup_system(
             },
             stylemap: plain.clone(),
             visible: Visible::transparent(),
+            ..Default::default()
         });
 }
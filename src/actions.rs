@@ -0,0 +1,420 @@
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use bevy::asset::HandleId;
+use bevy::input::Input;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::{HashMap, HashSet};
+use bevy::window::WindowFocused;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::exit::KeyChord;
+use crate::keyboard;
+use crate::suspend::Suspended;
+
+/// Something the player can do, like jumping or opening a menu, that keys and mouse buttons are bound to. This is
+/// usually a fieldless enum, and any type that fits the bounds is an action. Actions are deserialized from their
+/// names in a `.actions` file, so an enum's variants are written as strings like `"Jump"`.
+pub trait Action: Copy + Eq + Hash + Send + Sync + DeserializeOwned + 'static {}
+
+impl<T: Copy + Eq + Hash + Send + Sync + DeserializeOwned + 'static> Action for T {}
+
+/// A key or mouse button that triggers an action, along with the modifiers that have to be held with it. Extra
+/// modifiers being held doesn't stop a binding from matching, and letters match whatever their case so caps lock
+/// doesn't get in the way. Bind with the shift modifier to tell them apart.
+///
+/// In a `.actions` file bindings look like `Key(code: Char('w'))` or `Mouse(button: Left, modifiers: "ctrl+shift")`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key {
+        code: KeyCode,
+        #[serde(default = "KeyModifiers::empty", with = "modifier_parser")]
+        modifiers: KeyModifiers,
+    },
+    Mouse {
+        button: MouseButton,
+        #[serde(default = "KeyModifiers::empty", with = "modifier_parser")]
+        modifiers: KeyModifiers,
+    },
+}
+
+impl Binding {
+    pub fn key(code: KeyCode) -> Binding {
+        Binding::Key {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub fn mouse(button: MouseButton) -> Binding {
+        Binding::Mouse {
+            button,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub fn with_modifiers(self, modifiers: KeyModifiers) -> Binding {
+        match self {
+            Binding::Key { code, .. } => Binding::Key { code, modifiers },
+            Binding::Mouse { button, .. } => Binding::Mouse { button, modifiers },
+        }
+    }
+
+    /// The binding for a key press, so players can rebind an action by pressing the key they want for it. Releases
+    /// don't make a binding
+    pub fn from_key_event(event: &KeyEvent) -> Option<Binding> {
+        (event.kind != KeyEventKind::Release).then(|| Binding::key(event.code).with_modifiers(event.modifiers))
+    }
+
+    /// The binding for a mouse button being pressed, or None for any other mouse event
+    pub fn from_mouse_event(event: &MouseEvent) -> Option<Binding> {
+        match event.kind {
+            MouseEventKind::Down(button) => Some(Binding::mouse(button).with_modifiers(event.modifiers)),
+            _ => None,
+        }
+    }
+
+    fn matches_key(&self, event: &KeyEvent) -> bool {
+        let same_code = |code: &KeyCode| match (code, event.code) {
+            (KeyCode::Char(bound), KeyCode::Char(pressed)) => bound.to_lowercase().eq(pressed.to_lowercase()),
+            (code, pressed) => *code == pressed,
+        };
+        match self {
+            Binding::Key { code, modifiers } => {
+                event.kind != KeyEventKind::Release && same_code(code) && event.modifiers.contains(*modifiers)
+            }
+            Binding::Mouse { .. } => false,
+        }
+    }
+
+    fn matches_mouse(&self, pressed: MouseButton, held: KeyModifiers) -> bool {
+        match self {
+            Binding::Mouse { button, modifiers } => *button == pressed && held.contains(*modifiers),
+            Binding::Key { .. } => false,
+        }
+    }
+}
+
+impl From<KeyChord> for Binding {
+    fn from(chord: KeyChord) -> Self {
+        Binding::key(chord.code).with_modifiers(chord.modifiers)
+    }
+}
+
+mod modifier_parser {
+    use crossterm::event::KeyModifiers;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const NAMES: [(&str, KeyModifiers); 6] = [
+        ("shift", KeyModifiers::SHIFT),
+        ("ctrl", KeyModifiers::CONTROL),
+        ("alt", KeyModifiers::ALT),
+        ("super", KeyModifiers::SUPER),
+        ("hyper", KeyModifiers::HYPER),
+        ("meta", KeyModifiers::META),
+    ];
+
+    pub fn serialize<S>(modifiers: &KeyModifiers, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let names: Vec<&str> = NAMES
+            .iter()
+            .filter(|(_, modifier)| modifiers.contains(*modifier))
+            .map(|(name, _)| *name)
+            .collect();
+        serializer.serialize_str(&names.join("+"))
+    }
+
+    /// Modifiers are written as their names joined with `+`, like `"ctrl+shift"`
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<KeyModifiers, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        let mut modifiers = KeyModifiers::NONE;
+        for name in text.split('+').map(str::trim).filter(|name| !name.is_empty()) {
+            let (_, modifier) = NAMES
+                .iter()
+                .find(|(known, _)| known.eq_ignore_ascii_case(name))
+                .ok_or_else(|| D::Error::custom(format!("unknown modifier {:?}", name)))?;
+            modifiers |= *modifier;
+        }
+        Ok(modifiers)
+    }
+}
+
+/// Bindings for actions, loaded from a `.actions` file. The file is a RON map from action names to lists of
+/// bindings:
+///
+/// ```text
+/// {
+///     "Jump": [Key(code: Char(' ')), Key(code: Up)],
+///     "Fire": [Mouse(button: Left), Key(code: Char('f'), modifiers: "ctrl")],
+/// }
+/// ```
+///
+/// The names are only checked against an action type once an `InputMap` uses the bindings.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "2cba7545-96e9-4a5c-b505-2582b13392af"]
+pub struct ActionBindings(pub HashMap<String, Vec<Binding>>);
+
+/// Which bindings trigger each action. Insert one before adding the `ActionPlugin` to start with some bindings, and
+/// change it at any time to rebind actions.
+#[derive(Resource)]
+pub struct InputMap<A: Action> {
+    bindings: HashMap<A, Vec<Binding>>,
+    /// If set, the bindings are replaced with the ones in this asset once it's loaded, and again whenever it changes
+    pub source: Option<Handle<ActionBindings>>,
+    // The source that was last applied, so a new source is picked up even if it finished loading a while ago
+    applied: Option<HandleId>,
+}
+
+impl<A: Action> Default for InputMap<A> {
+    fn default() -> Self {
+        InputMap {
+            bindings: HashMap::default(),
+            source: None,
+            applied: None,
+        }
+    }
+}
+
+impl<A: Action> InputMap<A> {
+    pub fn new() -> InputMap<A> {
+        InputMap::default()
+    }
+
+    pub fn with_binding<B: Into<Binding>>(mut self, action: A, binding: B) -> InputMap<A> {
+        self.bind(action, binding);
+        self
+    }
+
+    pub fn with_source(mut self, source: Handle<ActionBindings>) -> InputMap<A> {
+        self.source = Some(source);
+        self
+    }
+
+    /// The bindings for an action
+    pub fn bindings(&self, action: A) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every action a binding triggers. Useful for warning players that a binding is already taken
+    pub fn actions(&self, binding: Binding) -> impl Iterator<Item = A> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| *action)
+    }
+
+    /// Adds a binding for an action, on top of the ones it already has
+    pub fn bind<B: Into<Binding>>(&mut self, action: A, binding: B) -> &mut Self {
+        let binding = binding.into();
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    pub fn unbind<B: Into<Binding>>(&mut self, action: A, binding: B) -> &mut Self {
+        let binding = binding.into();
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+        self
+    }
+
+    /// Replaces all of an action's bindings with a single one
+    pub fn rebind<B: Into<Binding>>(&mut self, action: A, binding: B) -> &mut Self {
+        self.clear(action).bind(action, binding)
+    }
+
+    /// Removes all of an action's bindings
+    pub fn clear(&mut self, action: A) -> &mut Self {
+        self.bindings.remove(&action);
+        self
+    }
+
+    /// Replaces every binding with the ones in `bindings`. If any of the names isn't an action, nothing is changed
+    pub fn load(&mut self, bindings: &ActionBindings) -> anyhow::Result<()> {
+        let mut loaded = HashMap::default();
+        for (name, bindings) in &bindings.0 {
+            let action: A = ron::de::from_str(name)
+                .map_err(|error| anyhow::anyhow!("{:?} isn't an action: {}", name, error))?;
+            loaded.insert(action, bindings.clone());
+        }
+        self.bindings = loaded;
+        Ok(())
+    }
+}
+
+/// Which actions are held, and which were pressed or released this frame, going by the `InputMap`. An action is held
+/// while any of its bindings is
+#[derive(Resource)]
+pub struct ActionState<A: Action> {
+    input: Input<A>,
+    // The key bindings being held, along with the key that was actually pressed for them, since letters match in
+    // either case
+    held_keys: HashMap<Binding, KeyCode>,
+    held_buttons: HashSet<Binding>,
+}
+
+impl<A: Action> Default for ActionState<A> {
+    fn default() -> Self {
+        ActionState {
+            input: Input::default(),
+            held_keys: HashMap::default(),
+            held_buttons: HashSet::default(),
+        }
+    }
+}
+
+impl<A: Action> ActionState<A> {
+    pub fn pressed(&self, action: A) -> bool {
+        self.input.pressed(action)
+    }
+
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.input.just_pressed(action)
+    }
+
+    pub fn just_released(&self, action: A) -> bool {
+        self.input.just_released(action)
+    }
+
+    pub fn get_pressed(&self) -> impl ExactSizeIterator<Item = &A> {
+        self.input.get_pressed()
+    }
+
+    pub fn get_just_pressed(&self) -> impl ExactSizeIterator<Item = &A> {
+        self.input.get_just_pressed()
+    }
+
+    pub fn get_just_released(&self) -> impl ExactSizeIterator<Item = &A> {
+        self.input.get_just_released()
+    }
+}
+
+/// Adds an `InputMap<A>` and an `ActionState<A>` that's kept up to date with it. Add it after the `CrosstermPlugin`,
+/// once for each action type.
+pub struct ActionPlugin<A>(PhantomData<A>);
+
+impl<A> Default for ActionPlugin<A> {
+    fn default() -> Self {
+        ActionPlugin(PhantomData)
+    }
+}
+
+impl<A: Action> Plugin for ActionPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap<A>>()
+            .init_resource::<ActionState<A>>()
+            // Actions are worked out from the keys that are held, so that has to be up to date first
+            .add_system(
+                apply_bindings::<A>
+                    .in_base_set(CoreSet::PreUpdate)
+                    .before(update_actions::<A>),
+            )
+            .add_system(
+                update_actions::<A>
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(keyboard::update_keyboard),
+            );
+    }
+}
+
+fn apply_bindings<A: Action>(
+    mut events: EventReader<AssetEvent<ActionBindings>>,
+    bindings: Res<Assets<ActionBindings>>,
+    mut map: ResMut<InputMap<A>>,
+) {
+    let Some(source) = map.source.clone() else {
+        return;
+    };
+    let modified = events.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => *handle == source,
+        AssetEvent::Removed { .. } => false,
+    });
+    if !modified && map.applied == Some(source.id()) {
+        return;
+    }
+
+    if let Some(loaded) = bindings.get(&source) {
+        map.applied = Some(source.id());
+        if let Err(error) = map.load(loaded) {
+            warn!("Could not use the action bindings: {}", error);
+        }
+    }
+}
+
+fn update_actions<A: Action>(
+    map: Res<InputMap<A>>,
+    keys: Res<Input<KeyCode>>,
+    mut key_events: EventReader<KeyEvent>,
+    mut mouse_events: EventReader<MouseEvent>,
+    mut suspended: EventReader<Suspended>,
+    mut focus: EventReader<WindowFocused>,
+    mut state: ResMut<ActionState<A>>,
+) {
+    let state = &mut *state;
+    state.input.clear();
+
+    // Nothing that was held going into a suspend or while the terminal was unfocused is known to still be held
+    let suspended = suspended.iter().count() > 0;
+    let unfocused = focus.iter().any(|event| !event.focused);
+    if suspended || unfocused {
+        state.held_keys.clear();
+        state.held_buttons.clear();
+    }
+
+    // Actions pressed this frame are pressed even if they were let go of before the frame came around, so quick taps
+    // aren't missed
+    let mut triggered = HashSet::default();
+    for event in key_events.iter() {
+        for (action, bindings) in &map.bindings {
+            for binding in bindings.iter().filter(|binding| binding.matches_key(event)) {
+                state.held_keys.insert(*binding, event.code);
+                triggered.insert(*action);
+            }
+        }
+    }
+    for event in mouse_events.iter() {
+        match event.kind {
+            MouseEventKind::Down(button) => {
+                for (action, bindings) in &map.bindings {
+                    for binding in bindings.iter().filter(|binding| binding.matches_mouse(button, event.modifiers)) {
+                        state.held_buttons.insert(*binding);
+                        triggered.insert(*action);
+                    }
+                }
+            }
+            MouseEventKind::Up(button) => {
+                state
+                    .held_buttons
+                    .retain(|binding| !matches!(binding, Binding::Mouse { button: held, .. } if *held == button));
+            }
+            _ => {}
+        }
+    }
+    state.held_keys.retain(|_, code| keys.pressed(*code));
+
+    for action in triggered {
+        state.input.press(action);
+    }
+    let held = |action: &A| {
+        map.bindings(*action)
+            .iter()
+            .any(|binding| state.held_keys.contains_key(binding) || state.held_buttons.contains(binding))
+    };
+    let released: Vec<A> = state.input.get_pressed().filter(|action| !held(action)).copied().collect();
+    for action in released {
+        state.input.release(action);
+    }
+}
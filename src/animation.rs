@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::window::RequestRedraw;
+
+use crate::components::{Sprite, StyleMap};
+
+/// What happens once the last frame of an animation has been shown
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AnimationMode {
+    /// Start again from the first frame
+    #[default]
+    Loop,
+    /// Play backwards to the first frame, then forwards again, and so on
+    PingPong,
+    /// Stop on the last frame
+    Once,
+}
+
+/// One frame of a `SpriteAnimation`. If the frame has no stylemap, the entity keeps whichever one it already has
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub sprite: Handle<Sprite>,
+    pub stylemap: Option<Handle<StyleMap>>,
+    pub duration: Duration,
+}
+
+impl Frame {
+    pub fn new(sprite: Handle<Sprite>, duration: Duration) -> Frame {
+        Frame {
+            sprite,
+            stylemap: None,
+            duration,
+        }
+    }
+
+    pub fn with_stylemap(mut self, stylemap: Handle<StyleMap>) -> Frame {
+        self.stylemap = Some(stylemap);
+        self
+    }
+}
+
+/// A sequence of sprites that are shown one after another by an `AnimationPlayer`. These can be built by hand from
+/// sprites that have been added to `Assets<Sprite>`, or loaded from a `.anim` file
+#[derive(Clone, Debug, Default, TypeUuid)]
+#[uuid = "5c3f0c2e-8a8f-4d0b-9a51-6f5e3e3c1b27"]
+pub struct SpriteAnimation {
+    pub frames: Vec<Frame>,
+    pub mode: AnimationMode,
+}
+
+impl SpriteAnimation {
+    pub fn new(frames: Vec<Frame>, mode: AnimationMode) -> SpriteAnimation {
+        SpriteAnimation { frames, mode }
+    }
+}
+
+/// Plays a `SpriteAnimation` on an entity by swapping out its `Handle<Sprite>`, and its `Handle<StyleMap>` for frames
+/// that have one
+#[derive(Clone, Debug, Component)]
+pub struct AnimationPlayer {
+    pub animation: Handle<SpriteAnimation>,
+    pub paused: bool,
+    frame: usize,
+    elapsed: Duration,
+    backwards: bool,
+    finished: bool,
+}
+
+impl AnimationPlayer {
+    pub fn new(animation: Handle<SpriteAnimation>) -> AnimationPlayer {
+        AnimationPlayer {
+            animation,
+            paused: false,
+            frame: 0,
+            elapsed: Duration::ZERO,
+            backwards: false,
+            finished: false,
+        }
+    }
+
+    /// The index of the frame being shown
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Whether an animation in `AnimationMode::Once` has reached its last frame
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Goes back to the first frame, and unpauses
+    pub fn restart(&mut self) {
+        *self = AnimationPlayer::new(self.animation.clone());
+    }
+
+    /// Moves on to the next frame
+    fn advance(&mut self, frames: usize, mode: AnimationMode) {
+        let last = frames - 1;
+        match mode {
+            AnimationMode::Loop => self.frame = if self.frame >= last { 0 } else { self.frame + 1 },
+            AnimationMode::Once if self.frame >= last => self.finished = true,
+            AnimationMode::Once => self.frame += 1,
+            AnimationMode::PingPong => {
+                // Turn around at either end, without showing the end frame twice
+                if self.backwards && self.frame == 0 || !self.backwards && self.frame >= last {
+                    self.backwards = !self.backwards;
+                }
+                self.frame = match self.backwards {
+                    true => self.frame.min(last).saturating_sub(1),
+                    false => (self.frame + 1).min(last),
+                };
+            }
+        }
+    }
+}
+
+pub(crate) fn animate(
+    time: Res<Time>,
+    animations: Res<Assets<SpriteAnimation>>,
+    mut players: Query<(&mut AnimationPlayer, &mut Handle<Sprite>, &mut Handle<StyleMap>)>,
+    mut redraw: EventWriter<RequestRedraw>,
+) {
+    let mut playing = false;
+    for (mut player, mut sprite, mut stylemap) in players.iter_mut() {
+        let animation = match animations.get(&player.animation) {
+            Some(animation) if !animation.frames.is_empty() => animation,
+            _ => continue,
+        };
+        let frames = animation.frames.len();
+
+        // The animation might have been hot reloaded with fewer frames
+        if player.frame >= frames {
+            player.frame = frames - 1;
+        }
+
+        if !player.paused && !player.finished {
+            player.elapsed += time.delta();
+            while !player.finished && player.elapsed >= animation.frames[player.frame].duration {
+                let duration = animation.frames[player.frame].duration;
+                player.elapsed -= duration;
+                player.advance(frames, animation.mode);
+
+                // Frames that take no time are only skipped one per update, otherwise this would never end
+                if duration.is_zero() {
+                    break;
+                }
+            }
+            playing |= !player.finished;
+        }
+
+        // Only swap the handles when they change, so change detection stays meaningful
+        let frame = &animation.frames[player.frame];
+        if *sprite != frame.sprite {
+            *sprite = frame.sprite.clone();
+        }
+        if let Some(frame_stylemap) = &frame.stylemap {
+            if *stylemap != *frame_stylemap {
+                *stylemap = frame_stylemap.clone();
+            }
+        }
+    }
+
+    // An app that only updates on demand has to keep updating while there are animations to show
+    if playing {
+        redraw.send(RequestRedraw);
+    }
+}
@@ -0,0 +1,313 @@
+use crossterm::style::{Attribute, Color};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::components::{grapheme_width, Colors, Sprite, Style, StyleMap};
+use crate::headless::{apply_sgr, Cell};
+
+// What bytes 0x80 to 0xFF are in code page 437, which most classic ANSI art is drawn in
+const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+// The width classic ANSI art wraps at when its SAUCE record doesn't say otherwise
+const CLASSIC_WIDTH: usize = 80;
+
+/// Plays ANSI art back into a grid of cells, the way a terminal would, and turns the result into a sprite and a
+/// stylemap that lines up with it.
+///
+/// Files that are valid utf-8 are read as utf-8 and never wrap. Anything else is classic code page 437 art, which wraps
+/// at 80 columns and uses bold to mean a bright foreground. A SAUCE record at the end of the file is stripped, and its
+/// width is used to wrap at if it has one.
+pub(crate) fn parse(bytes: &[u8]) -> (Sprite, StyleMap) {
+    let (bytes, sauce_width) = strip_sauce(bytes);
+
+    let (text, classic) = match std::str::from_utf8(bytes) {
+        Ok(text) => (text.to_string(), false),
+        Err(_) => (decode_cp437(bytes), true),
+    };
+    let wrap = match (sauce_width, classic) {
+        (Some(width), _) => Some(width),
+        (None, true) => Some(CLASSIC_WIDTH),
+        (None, false) => None,
+    };
+
+    let mut art = Art {
+        rows: Vec::new(),
+        x: 0,
+        y: 0,
+        saved: (0, 0),
+        pen: Cell::default(),
+        wrap,
+        classic,
+    };
+    art.play(&text);
+    art.finish()
+}
+
+/// Drops the SAUCE record and comments from the end of a file, returning what's left and the width from the record
+fn strip_sauce(bytes: &[u8]) -> (&[u8], Option<usize>) {
+    let mut width = None;
+    let mut bytes = bytes;
+    if bytes.len() >= 128 && bytes[bytes.len() - 128..].starts_with(b"SAUCE") {
+        let record = &bytes[bytes.len() - 128..];
+        // TInfo1, which is the width in characters for character based art
+        let info = u16::from_le_bytes([record[96], record[97]]) as usize;
+        if info > 0 {
+            width = Some(info);
+        }
+        bytes = &bytes[..bytes.len() - 128];
+    }
+
+    // Everything after the end of file marker is metadata
+    if let Some(end) = bytes.iter().position(|byte| *byte == 0x1a) {
+        bytes = &bytes[..end];
+    }
+
+    (bytes, width)
+}
+
+fn decode_cp437(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| match byte {
+            0x00..=0x7f => *byte as char,
+            _ => CP437_HIGH.chars().nth((byte - 0x80) as usize).unwrap(),
+        })
+        .collect()
+}
+
+struct Art {
+    // Every row is as long as the last column that was printed to. The second column of a wide grapheme is empty
+    rows: Vec<Vec<(String, Style)>>,
+    x: usize,
+    y: usize,
+    saved: (usize, usize),
+    pen: Cell,
+    wrap: Option<usize>,
+    classic: bool,
+}
+
+impl Art {
+    fn play(&mut self, text: &str) {
+        let mut rest = text;
+        while !rest.is_empty() {
+            if let Some(sequence) = rest.strip_prefix("\x1b[") {
+                // Parameters and intermediates run up to the final byte, which is in @ to ~
+                let end = sequence
+                    .find(|c: char| ('@'..='~').contains(&c))
+                    .unwrap_or(sequence.len());
+                let params = &sequence[..end];
+                if let Some(action) = sequence[end..].chars().next() {
+                    self.csi(params, action);
+                    rest = &sequence[end + action.len_utf8()..];
+                } else {
+                    rest = "";
+                }
+                continue;
+            }
+
+            let grapheme = rest.graphemes(true).next().unwrap();
+            rest = &rest[grapheme.len()..];
+            match grapheme {
+                // Any other escape sequence doesn't change what the art looks like, so just the escape is skipped
+                "\x1b" => {}
+                "\r" => self.x = 0,
+                "\n" | "\r\n" => {
+                    self.x = 0;
+                    self.y += 1;
+                }
+                "\t" => self.x = (self.x / 8 + 1) * 8,
+                _ if grapheme.chars().all(char::is_control) => {}
+                _ => self.print(grapheme),
+            }
+        }
+    }
+
+    fn csi(&mut self, params: &str, action: char) {
+        let mut numbers = params
+            .split(';')
+            .map(|number| number.parse::<usize>().unwrap_or(0));
+        let mut count = || numbers.next().unwrap_or(0).max(1);
+        match action {
+            'm' => apply_sgr(&mut self.pen, params),
+            'A' => self.y = self.y.saturating_sub(count()),
+            'B' => self.y += count(),
+            // Like a terminal, moving right stops at the edge instead of wrapping
+            'C' => {
+                self.x += count();
+                if let Some(wrap) = self.wrap {
+                    self.x = self.x.min(wrap - 1);
+                }
+            }
+            'D' => self.x = self.x.saturating_sub(count()),
+            'H' | 'f' => {
+                let y = count() - 1;
+                let x = count() - 1;
+                self.x = x;
+                self.y = y;
+            }
+            's' => self.saved = (self.x, self.y),
+            'u' => (self.x, self.y) = self.saved,
+            // Clearing the screen and other modes don't matter when there's nothing drawn underneath
+            _ => {}
+        }
+    }
+
+    fn print(&mut self, grapheme: &str) {
+        let width = grapheme_width(grapheme);
+        if let Some(wrap) = self.wrap {
+            if self.x + width > wrap {
+                self.x = 0;
+                self.y += 1;
+            }
+        }
+
+        let style = self.style();
+        if self.rows.len() <= self.y {
+            self.rows.resize_with(self.y + 1, Vec::new);
+        }
+        let row = &mut self.rows[self.y];
+        if row.len() < self.x + width {
+            row.resize(self.x + width, (" ".to_string(), Style::default()));
+        }
+
+        row[self.x] = (grapheme.to_string(), style);
+        if width == 2 {
+            row[self.x + 1] = (String::new(), style);
+        }
+        self.x += width;
+    }
+
+    /// The style the pen draws with. In classic art bold brightens the foreground instead of changing the font
+    fn style(&self) -> Style {
+        let mut attributes = self.pen.attributes;
+        let mut foreground = self.pen.foreground;
+        if self.classic && attributes.has(Attribute::Bold) {
+            let bright = match foreground {
+                Color::Black => Some(Color::DarkGrey),
+                Color::DarkRed => Some(Color::Red),
+                Color::DarkGreen => Some(Color::Green),
+                Color::DarkYellow => Some(Color::Yellow),
+                Color::DarkBlue => Some(Color::Blue),
+                Color::DarkMagenta => Some(Color::Magenta),
+                Color::DarkCyan => Some(Color::Cyan),
+                Color::Grey | Color::Reset => Some(Color::White),
+                _ => None,
+            };
+            if let Some(bright) = bright {
+                foreground = bright;
+                attributes.unset(Attribute::Bold);
+            }
+        }
+
+        // The default colors are left to the window, like any other unstyled sprite
+        let color = |color: Color| (color != Color::Reset).then_some(color);
+        Style::new(
+            Colors {
+                foreground: color(foreground),
+                background: color(self.pen.background),
+            },
+            attributes,
+        )
+    }
+
+    fn finish(self) -> (Sprite, StyleMap) {
+        let mut text = String::new();
+        let mut map = Vec::new();
+        for (index, row) in self.rows.into_iter().enumerate() {
+            if index > 0 {
+                text.push('\n');
+            }
+
+            let mut styles = Vec::new();
+            for (grapheme, style) in row {
+                text.push_str(&grapheme);
+                styles.push(style);
+            }
+            map.push(styles);
+        }
+
+        (Sprite::new(text), StyleMap::new(Style::default(), map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn foreground(stylemap: &StyleMap, x: usize, y: usize) -> Option<Color> {
+        stylemap.style_at(x, y).unwrap().colors.foreground
+    }
+
+    // Classic art with a SAUCE record on the end that gives its width
+    fn with_sauce(art: &[u8], width: u16) -> Vec<u8> {
+        let mut record = vec![0; 128];
+        record[..5].copy_from_slice(b"SAUCE");
+        record[96..98].copy_from_slice(&width.to_le_bytes());
+        [art, &[0x1a], &record].concat()
+    }
+
+    #[test]
+    fn sgr_colors() {
+        let (sprite, stylemap) = parse(b"\x1b[31mA\x1b[91mB\x1b[38;5;200mC\x1b[38;2;1;2;3;44mD\x1b[0mE");
+        assert_eq!(sprite.data(), "ABCDE");
+        let cases = [
+            (0, Some(Color::DarkRed)),
+            (1, Some(Color::Red)),
+            (2, Some(Color::AnsiValue(200))),
+            (3, Some(Color::Rgb { r: 1, g: 2, b: 3 })),
+            (4, None),
+        ];
+        for (x, color) in cases {
+            assert_eq!(foreground(&stylemap, x, 0), color, "column {}", x);
+        }
+        assert_eq!(stylemap.style_at(3, 0).unwrap().colors.background, Some(Color::DarkBlue));
+        assert_eq!(stylemap.style_at(4, 0).unwrap().colors.background, None);
+    }
+
+    #[test]
+    fn bold_is_bright_in_classic_art() {
+        // 0xdb isn't valid utf-8, so this is read as code page 437
+        let (sprite, stylemap) = parse(b"\x1b[1;31m\xdb\x1b[1;33mx");
+        assert_eq!(sprite.data(), "█x");
+        assert_eq!(foreground(&stylemap, 0, 0), Some(Color::Red));
+        assert_eq!(foreground(&stylemap, 1, 0), Some(Color::Yellow));
+        assert!(!stylemap.style_at(0, 0).unwrap().attributes.has(Attribute::Bold));
+
+        // Utf-8 art is left alone
+        let (_, stylemap) = parse("\x1b[1;31m█".as_bytes());
+        assert_eq!(foreground(&stylemap, 0, 0), Some(Color::DarkRed));
+        assert!(stylemap.style_at(0, 0).unwrap().attributes.has(Attribute::Bold));
+    }
+
+    #[test]
+    fn sauce_width_wraps() {
+        let (sprite, _) = parse(&with_sauce(b"ABCDEF", 4));
+        assert_eq!(sprite.data(), "ABCD\nEF");
+
+        // Without a width, classic art wraps at 80 columns
+        let art = [b"\xb0".repeat(81), b"x".to_vec()].concat();
+        let (sprite, _) = parse(&art);
+        assert_eq!(sprite.data(), format!("{}\n░x", "░".repeat(80)));
+    }
+
+    #[test]
+    fn cursor_forward_stops_at_the_edge() {
+        let (sprite, _) = parse(&with_sauce(b"A\x1b[10CBC", 5));
+        assert_eq!(sprite.data(), "A   B\nC");
+
+        // Art that doesn't wrap has no edge to stop at
+        let (sprite, _) = parse(b"A\x1b[10CB");
+        assert_eq!(sprite.data(), format!("A{}B", " ".repeat(10)));
+    }
+
+    #[test]
+    fn wide_graphemes_line_up_with_the_stylemap() {
+        let (sprite, stylemap) = parse("\x1b[31m漢\x1b[32mx".as_bytes());
+        assert_eq!(sprite.data(), "漢x");
+        assert_eq!(stylemap.map[0].len(), 3);
+
+        let columns: Vec<_> = sprite.columns(0).collect();
+        assert_eq!(columns, vec![(0, "漢"), (2, "x")]);
+        assert_eq!(foreground(&stylemap, 0, 0), Some(Color::DarkRed));
+        assert_eq!(foreground(&stylemap, 2, 0), Some(Color::DarkGreen));
+    }
+}
@@ -1,9 +1,18 @@
+use std::time::Duration;
+
 use bevy::{
-    asset::{AssetLoader, LoadContext, LoadedAsset},
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    prelude::{FromWorld, World},
     utils::BoxedFuture,
 };
 
+use crate::actions::ActionBindings;
+use crate::animation::{AnimationMode, Frame, SpriteAnimation};
+use crate::ansi_art;
+use crate::capabilities::{ColorDepth, TerminalCapabilities};
+use crate::half_block::{self, ImageOptions};
 use crate::components::{Sprite, StyleMap};
+use crate::legend;
 
 #[derive(Default)]
 pub struct SpriteLoader;
@@ -27,6 +36,7 @@ impl AssetLoader for SpriteLoader {
     }
 }
 
+/// Loads stylemaps stored as RON, or in the hand editable legend format if the file ends in `.legend.stylemap`
 #[derive(Default)]
 pub struct StyleMapLoader;
 
@@ -37,13 +47,415 @@ impl AssetLoader for StyleMapLoader {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
         Box::pin(async move {
-            let stylemap = ron::de::from_bytes::<StyleMap>(bytes)?;
+            let legend = load_context
+                .path()
+                .to_str()
+                .is_some_and(|path| path.ends_with(".legend.stylemap"));
+            let stylemap = if legend {
+                legend::parse_stylemap(std::str::from_utf8(bytes)?)?
+            } else {
+                ron::de::from_bytes::<StyleMap>(bytes)?
+            };
             load_context.set_default_asset(LoadedAsset::new(stylemap));
             Ok(())
         })
     }
 
     fn extensions(&self) -> &[&str] {
-        &["stylemap"]
+        &["stylemap", "legend.stylemap"]
+    }
+}
+
+/// Loads ANSI art, text with SGR escape sequences for colors and attributes in it. The sprite is the default asset,
+/// and the stylemap that goes with it is the `stylemap` labeled asset, so `art.ans` and `art.ans#stylemap`.
+///
+/// Load the `#stylemap` handle alongside the sprite and keep it from the start. A handle to just the file, like the
+/// ones `load_folder` returns, only keeps the sprite alive, and the stylemap is freed as soon as it's loaded
+#[derive(Default)]
+pub struct AnsiArtLoader;
+
+impl AssetLoader for AnsiArtLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let (sprite, stylemap) = ansi_art::parse(bytes);
+            load_context.set_labeled_asset("stylemap", LoadedAsset::new(stylemap));
+            load_context.set_default_asset(LoadedAsset::new(sprite));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ans"]
+    }
+}
+
+/// Loads a sprite and its stylemap from one file, so the two can't drift apart. The file is split into sections, the
+/// sprite's `[text]`, and the `[style]`, `[legend]` and `[map]` of a legend stylemap. An optional `[sprite]` section
+/// has settings, `anchor: <column>, <line>` for the cell that's drawn at the entity's position, and
+/// `transparent: <character>` for a character that's never drawn:
+///
+/// ```text
+/// [sprite]
+/// anchor: 2, 1
+/// transparent: .
+///
+/// [text]
+/// .(o).
+/// <-+->
+///
+/// [legend]
+/// e: fg yellow, bold
+///
+/// [map]
+///   e
+/// ```
+///
+/// The text is used as is, apart from blank lines at the end of it. The sprite is the default asset, and the stylemap
+/// is the `stylemap` labeled asset, so `thing.tsprite` and `thing.tsprite#stylemap`. Like with `AnsiArtLoader`, the
+/// `#stylemap` handle has to be held from the start or the stylemap is freed once it's loaded
+#[derive(Default)]
+pub struct TSpriteLoader;
+
+impl AssetLoader for TSpriteLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let (sprite, stylemap) = parse_tsprite(std::str::from_utf8(bytes)?)?;
+            load_context.set_labeled_asset("stylemap", LoadedAsset::new(stylemap));
+            load_context.set_default_asset(LoadedAsset::new(sprite));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tsprite"]
+    }
+}
+
+/// Splits a `.tsprite` file into its sprite and stylemap
+fn parse_tsprite(file: &str) -> anyhow::Result<(Sprite, StyleMap)> {
+    let sections = legend::sections(file, &["sprite", "text", "style", "legend", "map"], &["text", "map"])?;
+
+    let mut text: &[(usize, &str)] = &[];
+    let mut anchor = (0, 0);
+    let mut transparent = None;
+    for (name, lines) in &sections {
+        match *name {
+            "text" => text = lines,
+            "sprite" => {
+                for (number, line) in legend::settings(lines) {
+                    let (key, value) = line.split_once(':').ok_or_else(|| {
+                        anyhow::anyhow!("line {}: expected a setting like anchor: 0, 0", number)
+                    })?;
+                    match (key.trim(), value.trim()) {
+                        ("anchor", value) => {
+                            let (x, y) = value.split_once(',').ok_or_else(|| {
+                                anyhow::anyhow!("line {}: expected an anchor like 0, 0", number)
+                            })?;
+                            anchor = (x.trim().parse()?, y.trim().parse()?);
+                        }
+                        ("transparent", value) => {
+                            let mut chars = value.chars();
+                            transparent = chars.next().filter(|_| chars.next().is_none());
+                            if transparent.is_none() {
+                                anyhow::bail!("line {}: the transparent setting must be one character", number);
+                            }
+                        }
+                        _ => anyhow::bail!("line {}: unknown sprite setting: {}", number, line),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let end = text
+        .iter()
+        .rposition(|(_, line)| !line.trim().is_empty())
+        .map_or(0, |last| last + 1);
+    let text: Vec<&str> = text[..end].iter().map(|(_, line)| *line).collect();
+
+    let mut sprite = Sprite::new(text.join("\n")).with_anchor(anchor.0, anchor.1);
+    if let Some(transparent) = transparent {
+        sprite = sprite.with_transparent(transparent);
+    }
+    let stylemap = legend::stylemap_from_sections(&sections)?;
+    Ok((sprite, stylemap))
+}
+
+/// Loads images as sprites drawn with half blocks, two pixels to a cell. Every pixel is half a cell, and the colors are
+/// cut down to what the terminal can show with dithering. Use a `.timage` file to choose how the image is converted.
+/// The sprite is the default asset, and the stylemap is the `stylemap` labeled asset, so `logo.png` and
+/// `logo.png#stylemap`.
+///
+/// These are the same extensions bevy's own image loader uses, so this is only added when
+/// `CrosstermWindowSettings::set_load_images` is turned on
+pub struct HalfBlockLoader {
+    color_depth: ColorDepth,
+}
+
+impl FromWorld for HalfBlockLoader {
+    fn from_world(world: &mut World) -> Self {
+        HalfBlockLoader {
+            color_depth: terminal_color_depth(world),
+        }
+    }
+}
+
+impl AssetLoader for HalfBlockLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let image = image::load_from_memory(bytes)?;
+            let (sprite, stylemap) = half_block::convert(image, &ImageOptions::new(self.color_depth));
+            load_context.set_labeled_asset("stylemap", LoadedAsset::new(stylemap));
+            load_context.set_default_asset(LoadedAsset::new(sprite));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "bmp", "tga", "pbm", "pgm", "ppm", "pnm"]
+    }
+}
+
+/// Loads an image like `HalfBlockLoader` does, with settings for how it's converted. The settings are `key: value`
+/// lines, and only the image, relative to the settings file, is required:
+///
+/// ```text
+/// image: logo.png
+/// # Either can be left out, and the other is worked out so the image keeps its shape
+/// columns: 40
+/// lines: 10
+/// # 16, 256 or true, otherwise whatever the terminal can show
+/// colors: 256
+/// dither: false
+/// # Pixels less opaque than this are see-through, or 0 to draw them all
+/// alpha: 128
+/// ```
+pub struct TImageLoader {
+    color_depth: ColorDepth,
+}
+
+impl FromWorld for TImageLoader {
+    fn from_world(world: &mut World) -> Self {
+        TImageLoader {
+            color_depth: terminal_color_depth(world),
+        }
+    }
+}
+
+impl AssetLoader for TImageLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let string = std::str::from_utf8(bytes)?;
+            let mut options = ImageOptions::new(self.color_depth);
+            let (mut columns, mut lines) = (0, 0);
+            let mut path = None;
+            for line in string.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                let (key, value) = line
+                    .split_once(':')
+                    .ok_or_else(|| anyhow::anyhow!("Expected an image setting, found: {}", line))?;
+                match (key.trim(), value.trim()) {
+                    ("image", image) => path = Some(image.to_string()),
+                    ("columns", value) => columns = value.parse()?,
+                    ("lines", value) => lines = value.parse()?,
+                    ("colors", "16") => options.color_depth = ColorDepth::Ansi16,
+                    ("colors", "256") => options.color_depth = ColorDepth::Ansi256,
+                    ("colors", "true") => options.color_depth = ColorDepth::TrueColor,
+                    ("dither", value) => options.dither = value.parse()?,
+                    ("alpha", value) => options.alpha_threshold = value.parse()?,
+                    _ => anyhow::bail!("Unknown image setting: {}", line),
+                }
+            }
+            if (columns, lines) != (0, 0) {
+                options.size = Some((columns, lines));
+            }
+
+            let path = path.ok_or_else(|| anyhow::anyhow!("No image given"))?;
+            let path = load_context
+                .path()
+                .parent()
+                .map(|dir| dir.join(&path))
+                .unwrap_or_else(|| path.into());
+            // The image is read here rather than made a dependency, which would have the asset server load it as well,
+            // with whichever image loader it has or none at all
+            let image = image::load_from_memory(&load_context.read_asset_bytes(&path).await?)?;
+
+            let (sprite, stylemap) = half_block::convert(image, &options);
+            load_context.set_labeled_asset("stylemap", LoadedAsset::new(stylemap));
+            load_context.set_default_asset(LoadedAsset::new(sprite));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["timage"]
+    }
+}
+
+/// Images are converted for the colors the terminal can show, which the plugin works out before any loaders are added
+fn terminal_color_depth(world: &World) -> ColorDepth {
+    world
+        .get_resource::<TerminalCapabilities>()
+        .map_or(ColorDepth::TrueColor, |capabilities| capabilities.color_depth)
+}
+
+/// Loads a `SpriteAnimation` from a text file with all of the frames in it. Any lines before the first frame are
+/// settings, `mode: loop`, `mode: ping-pong` or `mode: once`, and `duration: <milliseconds>` for frames that don't
+/// have their own. Every frame starts with a line beginning with `---`, optionally followed by how long the frame is
+/// shown for in milliseconds and a stylemap to show it with, relative to the animation:
+///
+/// ```text
+/// mode: ping-pong
+/// duration: 100
+/// --- 250 blink.stylemap
+/// (o o)
+/// ---
+/// (- -)
+/// ```
+///
+/// Every frame's sprite is a labeled asset, `frame0`, `frame1` and so on.
+#[derive(Default)]
+pub struct SpriteAnimationLoader;
+
+impl AssetLoader for SpriteAnimationLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let string = std::str::from_utf8(bytes)?;
+            let mut mode = AnimationMode::default();
+            let mut default_duration = Duration::from_millis(100);
+
+            // Each frame's delimiter line and the lines that make up the frame
+            let mut frames: Vec<(&str, Vec<&str>)> = Vec::new();
+            for line in string.lines() {
+                if let Some(delimiter) = line.strip_prefix("---") {
+                    frames.push((delimiter, Vec::new()));
+                } else if let Some((_, lines)) = frames.last_mut() {
+                    lines.push(line);
+                } else if let Some((key, value)) = line.split_once(':') {
+                    match (key.trim(), value.trim()) {
+                        ("mode", "loop") => mode = AnimationMode::Loop,
+                        ("mode", "ping-pong") => mode = AnimationMode::PingPong,
+                        ("mode", "once") => mode = AnimationMode::Once,
+                        ("duration", millis) => default_duration = Duration::from_millis(millis.parse()?),
+                        _ => anyhow::bail!("Unknown animation setting: {}", line),
+                    }
+                } else if !line.trim().is_empty() {
+                    anyhow::bail!("Expected an animation setting or a frame, found: {}", line);
+                }
+            }
+
+            let mut animation = SpriteAnimation::new(Vec::new(), mode);
+            let mut dependencies = Vec::new();
+            for (index, (delimiter, lines)) in frames.into_iter().enumerate() {
+                let mut args = delimiter.split_whitespace();
+                let duration = match args.next() {
+                    Some(millis) => Duration::from_millis(millis.parse()?),
+                    None => default_duration,
+                };
+
+                let sprite = Sprite::new(lines.join("\n"));
+                let sprite = load_context.set_labeled_asset(&format!("frame{}", index), LoadedAsset::new(sprite));
+                let mut frame = Frame::new(sprite, duration);
+
+                if let Some(stylemap) = args.next() {
+                    let path = load_context
+                        .path()
+                        .parent()
+                        .map(|dir| dir.join(stylemap))
+                        .unwrap_or_else(|| stylemap.into());
+                    let path = AssetPath::new(path, None);
+                    frame = frame.with_stylemap(load_context.get_handle(path.clone()));
+                    dependencies.push(path);
+                }
+
+                animation.frames.push(frame);
+            }
+
+            load_context.set_default_asset(LoadedAsset::new(animation).with_dependencies(dependencies));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim"]
+    }
+}
+
+/// Loads action bindings stored as RON. See `ActionBindings`
+#[derive(Default)]
+pub struct ActionBindingsLoader;
+
+impl AssetLoader for ActionBindingsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let bindings = ron::de::from_bytes::<ActionBindings>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(bindings));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["actions"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Attribute;
+
+    use super::*;
+
+    #[test]
+    fn tsprite_settings() {
+        let file = "[sprite]\nanchor: 2, 1\ntransparent: .\n\n[text]\n.(o).\n<-+->\n\n[legend]\ne: bold\n[map]\n  e";
+        let (sprite, stylemap) = parse_tsprite(file).unwrap();
+        assert_eq!(sprite.data(), ".(o).\n<-+->");
+        assert_eq!(sprite.anchor(), (2, 1));
+        assert_eq!(sprite.transparent(), Some('.'));
+        assert!(stylemap.style_at(2, 0).unwrap().attributes.has(Attribute::Bold));
+        assert!(!stylemap.style_at(1, 0).unwrap().attributes.has(Attribute::Bold));
+    }
+
+    #[test]
+    fn tsprite_setting_errors() {
+        let cases = [
+            ("[sprite]\nanchor 2, 1\n", "line 2: expected a setting like anchor: 0, 0"),
+            ("[sprite]\nanchor: 2\n", "line 2: expected an anchor like 0, 0"),
+            ("[sprite]\n\ntransparent: ..\n", "line 3: the transparent setting must be one character"),
+            ("[sprite]\ntransparent:\n", "line 2: the transparent setting must be one character"),
+            ("[sprite]\nsize: 2, 2\n", "line 2: unknown sprite setting: size: 2, 2"),
+        ];
+        for (file, message) in cases {
+            assert_eq!(parse_tsprite(file).err().unwrap().to_string(), message, "{:?}", file);
+        }
+        assert!(parse_tsprite("[sprite]\nanchor: a, 1\n").is_err());
     }
 }
@@ -0,0 +1,224 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::Resource;
+use crossterm::event::KeyboardEnhancementFlags;
+use crossterm::QueueableCommand;
+
+/// Something the plugin can render into. Every crossterm command the plugin emits is queued into the backend with
+/// `QueueableCommand`, so a backend is just a `Write` sink that also knows how big the screen it represents is.
+pub trait TerminalBackend: Write + Send + Sync + 'static {
+    /// The size of the screen in (columns, rows)
+    fn size(&self) -> std::io::Result<(u16, u16)>;
+
+    /// Raw mode only makes sense for a real tty, so backends that aren't one can leave this alone
+    fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Where the cursor is in (column, row). Only used to find where an inline viewport starts, so backends that can't
+    /// tell can leave this alone
+    fn cursor_position(&mut self) -> std::io::Result<(u16, u16)> {
+        Ok((0, 0))
+    }
+
+    /// Whether this backend is the process' own terminal, which has to be put back the way it was found even if the
+    /// app panics or is killed
+    fn is_tty(&self) -> bool {
+        false
+    }
+
+    /// Whether the terminal understands crossterm's keyboard enhancement flags, which make it report when keys are
+    /// repeated and released rather than only when they're pressed
+    fn supports_keyboard_enhancement(&mut self) -> std::io::Result<bool> {
+        Ok(false)
+    }
+}
+
+/// The default backend, which renders to the process' stdout
+#[derive(Debug)]
+pub struct StdoutBackend(std::io::Stdout);
+
+impl Default for StdoutBackend {
+    fn default() -> Self {
+        StdoutBackend(std::io::stdout())
+    }
+}
+
+impl Write for StdoutBackend {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl TerminalBackend for StdoutBackend {
+    fn size(&self) -> std::io::Result<(u16, u16)> {
+        crossterm::terminal::size()
+    }
+
+    fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+        crossterm::terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+        crossterm::terminal::disable_raw_mode()
+    }
+
+    fn cursor_position(&mut self) -> std::io::Result<(u16, u16)> {
+        crossterm::cursor::position()
+    }
+
+    fn is_tty(&self) -> bool {
+        true
+    }
+
+    fn supports_keyboard_enhancement(&mut self) -> std::io::Result<bool> {
+        crossterm::terminal::supports_keyboard_enhancement()
+    }
+}
+
+/// A backend that collects everything written to it in memory. Clones share the same buffer, so keep a clone around
+/// to inspect the output after handing the backend to the plugin.
+#[derive(Clone, Debug)]
+pub struct MemoryBackend {
+    width: u16,
+    height: u16,
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl MemoryBackend {
+    pub fn new(width: u16, height: u16) -> MemoryBackend {
+        MemoryBackend {
+            width,
+            height,
+            buffer: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// A copy of all the bytes written so far
+    pub fn contents(&self) -> Vec<u8> {
+        self.buffer.lock().unwrap().clone()
+    }
+
+    /// Returns all the bytes written so far and empties the buffer
+    pub fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.buffer.lock().unwrap())
+    }
+
+    pub fn set_size(&mut self, width: u16, height: u16) {
+        self.width = width;
+        self.height = height;
+    }
+}
+
+impl Write for MemoryBackend {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl TerminalBackend for MemoryBackend {
+    fn size(&self) -> std::io::Result<(u16, u16)> {
+        Ok((self.width, self.height))
+    }
+}
+
+/// The backend the plugin renders into. Insert one before adding the `CrosstermPlugin` to render somewhere other than
+/// stdout.
+#[derive(Resource)]
+pub struct Terminal {
+    backend: Box<dyn TerminalBackend>,
+    // Whether keyboard enhancement flags have been pushed, and so have to be popped again when leaving the terminal
+    keyboard_enhanced: bool,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Terminal::new(StdoutBackend::default())
+    }
+}
+
+impl Terminal {
+    pub fn new<B: TerminalBackend>(backend: B) -> Terminal {
+        Terminal {
+            backend: Box::new(backend),
+            keyboard_enhanced: false,
+        }
+    }
+
+    pub fn size(&self) -> std::io::Result<(u16, u16)> {
+        self.backend.size()
+    }
+
+    pub fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+        self.backend.enable_raw_mode()
+    }
+
+    pub fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+        self.backend.disable_raw_mode()
+    }
+
+    pub fn cursor_position(&mut self) -> std::io::Result<(u16, u16)> {
+        self.backend.cursor_position()
+    }
+
+    pub fn is_tty(&self) -> bool {
+        self.backend.is_tty()
+    }
+
+    pub fn supports_keyboard_enhancement(&mut self) -> std::io::Result<bool> {
+        self.backend.supports_keyboard_enhancement()
+    }
+
+    /// Whether the terminal has been asked to report key repeats and releases
+    pub fn keyboard_enhanced(&self) -> bool {
+        self.keyboard_enhanced
+    }
+
+    /// Asks the terminal to report key repeats and releases if it can, returning whether it will. Every key is reported
+    /// as an escape code, since that's the only way to hear about plain text keys being released, along with the
+    /// character shift turns it into so text comes through as it was typed
+    pub(crate) fn enable_keyboard_enhancement(&mut self) -> std::io::Result<bool> {
+        if !self.keyboard_enhanced && self.supports_keyboard_enhancement()? {
+            self.queue(crossterm::event::PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                    | KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS,
+            ))?;
+            self.keyboard_enhanced = true;
+        }
+        Ok(self.keyboard_enhanced)
+    }
+
+    pub(crate) fn disable_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        if self.keyboard_enhanced {
+            self.queue(crossterm::event::PopKeyboardEnhancementFlags)?;
+            self.keyboard_enhanced = false;
+        }
+        Ok(())
+    }
+}
+
+impl Write for Terminal {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.backend.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.backend.flush()
+    }
+}
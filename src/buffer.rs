@@ -0,0 +1,153 @@
+use bevy::prelude::Resource;
+use crossterm::style::{Attribute, Attributes};
+
+use crate::components::{grapheme_width, Colors, Style};
+
+/// One character cell of the screen, with all of its styling resolved
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ScreenCell {
+    pub grapheme: String,
+    pub colors: Colors,
+    pub attributes: Attributes,
+}
+
+impl ScreenCell {
+    fn blank(colors: Colors) -> ScreenCell {
+        ScreenCell {
+            grapheme: " ".to_string(),
+            colors,
+            attributes: Attributes::default(),
+        }
+    }
+}
+
+/// A double buffered copy of the screen. Every frame the back buffer is composited from scratch, and only the cells that
+/// differ from the front buffer (what the terminal is currently showing) get sent to the terminal.
+#[derive(Resource)]
+pub(crate) struct ScreenBuffer {
+    width: u16,
+    height: u16,
+    front: Vec<ScreenCell>,
+    back: Vec<ScreenCell>,
+    // When set, the front buffer can't be trusted and every cell is sent on the next flush
+    pub full_redraw: bool,
+}
+
+impl Default for ScreenBuffer {
+    fn default() -> Self {
+        ScreenBuffer {
+            width: 0,
+            height: 0,
+            front: Vec::new(),
+            back: Vec::new(),
+            full_redraw: true,
+        }
+    }
+}
+
+impl ScreenBuffer {
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Resizes both buffers if the size changed. What was on the screen is lost, so this forces a full redraw
+    pub fn resize(&mut self, width: u16, height: u16) {
+        if width == self.width && height == self.height {
+            return;
+        }
+
+        let blank = ScreenCell::blank(Colors::term_colors());
+        let len = width as usize * height as usize;
+        self.width = width;
+        self.height = height;
+        self.front = vec![blank.clone(); len];
+        self.back = vec![blank; len];
+        self.full_redraw = true;
+    }
+
+    /// Blanks out the back buffer so the next frame can be composited into it
+    pub fn clear(&mut self, colors: Colors) {
+        for cell in self.back.iter_mut() {
+            cell.grapheme.clear();
+            cell.grapheme.push(' ');
+            cell.colors = colors;
+            cell.attributes = Attributes::default();
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        Some(y as usize * self.width as usize + x as usize)
+    }
+
+    /// Paints a grapheme into the back buffer. A wide grapheme also takes up the cell to its right, which is left with
+    /// an empty grapheme. Anything off the screen is ignored
+    pub fn set(&mut self, x: i32, y: i32, grapheme: &str, style: &Style) {
+        let idx = match self.index(x, y) {
+            Some(idx) => idx,
+            None => return,
+        };
+
+        let wide = grapheme_width(grapheme) == 2;
+        if wide && self.index(x + 1, y).is_none() {
+            // Half a wide grapheme can't be shown, so it's a space instead
+            self.set(x, y, " ", style);
+            return;
+        }
+
+        self.split_wide(idx);
+        self.fill(idx, grapheme, style);
+        if wide {
+            self.split_wide(idx + 1);
+            self.fill(idx + 1, "", style);
+        }
+    }
+
+    fn fill(&mut self, idx: usize, grapheme: &str, style: &Style) {
+        let cell = &mut self.back[idx];
+        cell.grapheme.clear();
+        cell.grapheme.push_str(grapheme);
+        cell.colors = style.colors;
+        // Reset is an instruction for the terminal, not something the cell looks like
+        cell.attributes = style.attributes;
+        cell.attributes.unset(Attribute::Reset);
+    }
+
+    /// If the cell is one half of a wide grapheme that's about to be painted over, the other half can't be displayed
+    /// on its own any more so it becomes a space
+    fn split_wide(&mut self, idx: usize) {
+        let width = self.width as usize;
+        if self.back[idx].grapheme.is_empty() {
+            // The second half of a wide grapheme is never in the first column, so the first half is on this row
+            let head = &mut self.back[idx - 1].grapheme;
+            head.clear();
+            head.push(' ');
+        } else if !(idx + 1).is_multiple_of(width) && self.back[idx + 1].grapheme.is_empty() {
+            self.back[idx + 1].grapheme.push(' ');
+        }
+    }
+
+    /// All the cells in the back buffer which need to be sent to the terminal, in the order they should be written.
+    /// The second half of a wide grapheme is included whenever the first half is, and shouldn't be printed
+    pub fn changes(&self) -> impl Iterator<Item = (u16, u16, &ScreenCell)> {
+        let width = self.width as usize;
+        self.back
+            .iter()
+            .zip(self.front.iter())
+            .enumerate()
+            .filter(move |(_, (back, front))| self.full_redraw || back != front)
+            .map(move |(idx, (back, _))| ((idx % width) as u16, (idx / width) as u16, back))
+    }
+
+    /// The back buffer is on the screen now, so it becomes the front buffer
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.front, &mut self.back);
+        self.full_redraw = false;
+    }
+}
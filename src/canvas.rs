@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+
+use crate::components::{Color, GlobalPosition, Position, Sprite, Style, StyleMap, Visible};
+use crate::shapes::{self, EMPTY};
+use crate::systems;
+
+// The quadrant block for each combination of pixels, where the top left pixel is bit 0, top right is bit 1, bottom left
+// is bit 2 and bottom right is bit 3
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+// The bit for each dot of a braille character, indexed by row and then column. The dots aren't numbered in order,
+// since the bottom row was added to braille later
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// How a canvas splits each cell up into pixels
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CanvasMode {
+    /// 2x4 pixels per cell, drawn with braille characters
+    #[default]
+    Braille,
+    /// 2x2 pixels per cell, drawn with quadrant blocks
+    Quadrant,
+    /// 1x2 pixels per cell, drawn with half blocks
+    HalfBlock,
+}
+
+impl CanvasMode {
+    /// How many pixels wide and high a cell is
+    pub fn pixels_per_cell(&self) -> (usize, usize) {
+        match self {
+            CanvasMode::Braille => (2, 4),
+            CanvasMode::Quadrant => (2, 2),
+            CanvasMode::HalfBlock => (1, 2),
+        }
+    }
+
+    /// The character for a cell, given which of its pixels are set in reading order
+    fn character(&self, pixels: &[bool]) -> char {
+        match self {
+            CanvasMode::Braille => {
+                let bits = pixels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, set)| **set)
+                    .map(|(index, _)| BRAILLE_DOTS[index / 2][index % 2])
+                    .sum::<u32>();
+                char::from_u32(0x2800 + bits).unwrap()
+            }
+            CanvasMode::Quadrant | CanvasMode::HalfBlock => {
+                let bits = pixels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, set)| **set)
+                    .map(|(index, _)| 1 << index)
+                    .sum::<usize>();
+                match self {
+                    CanvasMode::HalfBlock => [' ', '▀', '▄', '█'][bits],
+                    _ => QUADRANTS[bits],
+                }
+            }
+        }
+    }
+}
+
+/// A grid of pixels drawn into the entity's sprite whenever it changes, several to a cell, for plotting things in more
+/// detail than whole cells allow. Pixel 0, 0 is the top left of the canvas.
+///
+/// Each cell can have a color, which all of the pixels in it are drawn in. Cells without any pixels set are left
+/// see-through, so a canvas can be drawn over other sprites.
+///
+/// Like `Text`, the canvas's sprite and stylemap belong to it and shouldn't be shared with other entities.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Component)]
+pub struct Canvas {
+    mode: CanvasMode,
+    columns: usize,
+    lines: usize,
+    pixels: Vec<bool>,
+    colors: Vec<Option<Color>>,
+    /// If set, the cells that pixels are set in are given this color
+    pub pen: Option<Color>,
+}
+
+impl Canvas {
+    /// A braille canvas that's columns by lines cells big
+    pub fn new(columns: usize, lines: usize) -> Canvas {
+        Canvas::with_mode(columns, lines, CanvasMode::Braille)
+    }
+
+    pub fn with_mode(columns: usize, lines: usize, mode: CanvasMode) -> Canvas {
+        let (cell_width, cell_height) = mode.pixels_per_cell();
+        Canvas {
+            mode,
+            columns,
+            lines,
+            pixels: vec![false; columns * cell_width * lines * cell_height],
+            colors: vec![None; columns * lines],
+            pen: None,
+        }
+    }
+
+    pub fn mode(&self) -> CanvasMode {
+        self.mode
+    }
+
+    /// How many cells wide the canvas is
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// How many cells high the canvas is
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// How many pixels wide the canvas is
+    pub fn width(&self) -> usize {
+        self.columns * self.mode.pixels_per_cell().0
+    }
+
+    /// How many pixels high the canvas is
+    pub fn height(&self) -> usize {
+        self.lines * self.mode.pixels_per_cell().1
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let in_bounds = x >= 0 && y >= 0 && (x as usize) < self.width() && (y as usize) < self.height();
+        in_bounds.then(|| y as usize * self.width() + x as usize)
+    }
+
+    fn cell_index(&self, x: i32, y: i32) -> Option<usize> {
+        let (cell_width, cell_height) = self.mode.pixels_per_cell();
+        self.index(x, y)
+            .map(|_| y as usize / cell_height * self.columns + x as usize / cell_width)
+    }
+
+    /// Whether a pixel is set. Pixels off the canvas never are
+    pub fn get(&self, x: i32, y: i32) -> bool {
+        self.index(x, y).is_some_and(|index| self.pixels[index])
+    }
+
+    /// Sets a pixel, coloring its cell with the pen if there is one. Pixels off the canvas are ignored
+    pub fn set(&mut self, x: i32, y: i32) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = true;
+            if let Some(pen) = self.pen {
+                self.set_color(x, y, pen);
+            }
+        }
+    }
+
+    /// Clears a pixel. Pixels off the canvas are ignored
+    pub fn unset(&mut self, x: i32, y: i32) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = false;
+        }
+    }
+
+    /// Sets the color of the cell a pixel is in
+    pub fn set_color(&mut self, x: i32, y: i32, color: Color) {
+        if let Some(cell) = self.cell_index(x, y) {
+            self.colors[cell] = Some(color);
+        }
+    }
+
+    /// Sets every pixel on a straight line between two pixels, including both ends
+    pub fn line(&mut self, from: (i32, i32), to: (i32, i32)) {
+        for (x, y) in shapes::line(from, to) {
+            self.set(x, y);
+        }
+    }
+
+    /// Clears every pixel and color
+    pub fn clear(&mut self) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = false);
+        self.colors.iter_mut().for_each(|color| *color = None);
+    }
+
+    /// Draws the canvas into a sprite and a stylemap
+    pub fn layout(&self) -> (Sprite, StyleMap) {
+        let (cell_width, cell_height) = self.mode.pixels_per_cell();
+        let mut data = String::new();
+        let mut map = Vec::new();
+        for line in 0..self.lines {
+            if line > 0 {
+                data.push('\n');
+            }
+
+            let mut styles = Vec::new();
+            for column in 0..self.columns {
+                let mut pixels = Vec::new();
+                for y in 0..cell_height {
+                    for x in 0..cell_width {
+                        pixels.push(self.get((column * cell_width + x) as i32, (line * cell_height + y) as i32));
+                    }
+                }
+
+                if pixels.contains(&true) {
+                    data.push(self.mode.character(&pixels));
+                } else {
+                    data.push(EMPTY);
+                }
+                styles.push(match self.colors[line * self.columns + column] {
+                    Some(color) => Style::with_fg(color),
+                    None => Style::default(),
+                });
+            }
+            map.push(styles);
+        }
+
+        (Sprite::new(data).with_transparent(EMPTY), StyleMap::new(Style::default(), map))
+    }
+}
+
+/// An entity that displays a canvas. See `Canvas`
+#[derive(Bundle, Default)]
+pub struct CanvasBundle {
+    pub canvas: Canvas,
+    pub sprite: Handle<Sprite>,
+    pub position: Position,
+    pub global_position: GlobalPosition,
+    pub stylemap: Handle<StyleMap>,
+    pub visible: Visible,
+}
+
+pub(crate) fn layout_canvases(
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+    mut canvases: Query<(&Canvas, &mut Handle<Sprite>, &mut Handle<StyleMap>), Changed<Canvas>>,
+) {
+    for (canvas, mut sprite_handle, mut stylemap_handle) in canvases.iter_mut() {
+        let (sprite, stylemap) = canvas.layout();
+        systems::replace_assets(
+            (&mut sprites, &mut sprite_handle),
+            (&mut stylemaps, &mut stylemap_handle),
+            sprite,
+            stylemap,
+        );
+    }
+}
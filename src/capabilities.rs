@@ -0,0 +1,367 @@
+use std::path::PathBuf;
+
+use bevy::prelude::Resource;
+
+use crate::components::{Color, Colors};
+
+/// How many colors a terminal can show. Ordered from least to most capable
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ColorDepth {
+    /// No colors at all, only attributes. Either the terminal can't do color or the user asked for none with NO_COLOR
+    NoColor,
+    /// The 16 named colors
+    Ansi16,
+    /// The 256 color palette
+    Ansi256,
+    /// 24-bit rgb
+    TrueColor,
+}
+
+// The 16 named colors and what they usually look like, in palette order
+const ANSI_PALETTE: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// The levels each channel can take in the 6x6x6 color cube that makes up palette entries 16 to 231
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Works out the color depth from the environment: NO_COLOR, then COLORTERM, then the terminfo entry for TERM,
+    /// and if there isn't one, guesses from the name in TERM
+    pub fn detect() -> ColorDepth {
+        // See https://no-color.org
+        if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorDepth::NoColor;
+        }
+
+        if let Ok(colorterm) = std::env::var("COLORTERM") {
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                return ColorDepth::TrueColor;
+            }
+        }
+
+        let term = match std::env::var("TERM") {
+            Ok(term) if !term.is_empty() => term,
+            // Windows terminals don't set TERM, and the ones crossterm supports can all do 24-bit color
+            _ if cfg!(windows) => return ColorDepth::TrueColor,
+            _ => return ColorDepth::Ansi16,
+        };
+
+        if term == "dumb" {
+            return ColorDepth::NoColor;
+        }
+        if term.ends_with("-direct") {
+            return ColorDepth::TrueColor;
+        }
+
+        match terminfo_colors(&term) {
+            Some(colors) if colors >= 1 << 24 => ColorDepth::TrueColor,
+            Some(colors) if colors >= 256 => ColorDepth::Ansi256,
+            Some(colors) if colors >= 8 => ColorDepth::Ansi16,
+            Some(_) => ColorDepth::NoColor,
+            None if term.contains("256color") => ColorDepth::Ansi256,
+            None => ColorDepth::Ansi16,
+        }
+    }
+
+    /// The closest color to `color` that can be shown at this depth. Reset is always left alone
+    pub fn downsample(&self, color: Color) -> Color {
+        match (self, color) {
+            (_, Color::Reset) => Color::Reset,
+            (ColorDepth::NoColor, _) => Color::Reset,
+            (ColorDepth::TrueColor, color) => color,
+            (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(nearest_ansi256((r, g, b))),
+            (ColorDepth::Ansi256, color) => color,
+            (ColorDepth::Ansi16, Color::Rgb { r, g, b }) => nearest_ansi16((r, g, b)).0,
+            (ColorDepth::Ansi16, Color::AnsiValue(value)) if value < 16 => ANSI_PALETTE[value as usize].0,
+            (ColorDepth::Ansi16, Color::AnsiValue(value)) => nearest_ansi16(ansi256_rgb(value)).0,
+            (ColorDepth::Ansi16, color) => color,
+        }
+    }
+
+    /// The closest color to an rgb value that can be shown at this depth, along with the rgb value it usually looks
+    /// like. Without any colors, the rgb value is left as it is
+    pub(crate) fn quantize(&self, rgb: (u8, u8, u8)) -> (Color, (u8, u8, u8)) {
+        match self {
+            ColorDepth::NoColor | ColorDepth::TrueColor => (
+                Color::Rgb {
+                    r: rgb.0,
+                    g: rgb.1,
+                    b: rgb.2,
+                },
+                rgb,
+            ),
+            ColorDepth::Ansi256 => {
+                let value = nearest_ansi256(rgb);
+                (Color::AnsiValue(value), ansi256_rgb(value))
+            }
+            ColorDepth::Ansi16 => nearest_ansi16(rgb),
+        }
+    }
+}
+
+/// What the terminal the plugin is drawing to can do. Colors are downsampled to fit before they're sent to it, so
+/// apps can use any color they like.
+///
+/// This is detected from the environment when the plugin is added, unless a color depth was chosen with
+/// `CrosstermWindowSettings::set_color_depth`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+pub struct TerminalCapabilities {
+    pub color_depth: ColorDepth,
+}
+
+impl Default for TerminalCapabilities {
+    fn default() -> Self {
+        TerminalCapabilities::detect()
+    }
+}
+
+impl TerminalCapabilities {
+    pub fn detect() -> TerminalCapabilities {
+        TerminalCapabilities {
+            color_depth: ColorDepth::detect(),
+        }
+    }
+
+    /// Both colors downsampled to what the terminal can show
+    pub fn downsample(&self, colors: Colors) -> Colors {
+        Colors {
+            foreground: colors.foreground.map(|color| self.color_depth.downsample(color)),
+            background: colors.background.map(|color| self.color_depth.downsample(color)),
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(r1, r2) + channel(g1, g2) + channel(b1, b2)
+}
+
+/// The closest of the 16 named colors, and what it looks like
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> (Color, (u8, u8, u8)) {
+    *ANSI_PALETTE
+        .iter()
+        .min_by_key(|(_, palette)| distance(rgb, *palette))
+        .unwrap()
+}
+
+/// The closest entry in either the color cube or the grey ramp. The first 16 entries are skipped since terminals
+/// don't agree on what they look like
+fn nearest_ansi256((r, g, b): (u8, u8, u8)) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - channel as i32).abs())
+            .unwrap() as u8
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+
+    // The grey ramp goes from 8 to 238 in steps of 10
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance((r, g, b), ansi256_rgb(grey)) < distance((r, g, b), ansi256_rgb(cube)) {
+        grey
+    } else {
+        cube
+    }
+}
+
+fn ansi256_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => ANSI_PALETTE[value as usize].1,
+        16..=231 => {
+            let index = value - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (value - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// Looks up the number of colors in the terminfo entry for a terminal. An entry without a number of colors means
+/// the terminal is monochrome, so that's 0
+fn terminfo_colors(term: &str) -> Option<i32> {
+    let first = term.chars().next()?;
+
+    let mut dirs = Vec::new();
+    if let Some(dir) = std::env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+    if let Ok(list) = std::env::var("TERMINFO_DIRS") {
+        dirs.extend(list.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+    }
+    dirs.extend(
+        ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+            .iter()
+            .map(PathBuf::from),
+    );
+
+    // Entries are filed under their first letter, or on some systems under its hex code
+    let subdirs = [first.to_string(), format!("{:x}", first as u32)];
+    let entry = dirs.iter().find_map(|dir| {
+        subdirs
+            .iter()
+            .find_map(|subdir| std::fs::read(dir.join(subdir).join(term)).ok())
+    })?;
+
+    parse_terminfo_colors(&entry)
+}
+
+/// Reads the colors number out of a compiled terminfo entry, see term(5)
+fn parse_terminfo_colors(entry: &[u8]) -> Option<i32> {
+    // The index of the colors capability in the numbers section
+    const COLORS: usize = 13;
+
+    let header = |i: usize| {
+        entry
+            .get(i * 2..i * 2 + 2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+    };
+
+    // The original format stores numbers in 16 bits, the extended one in 32
+    let number_size = match header(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let names_size = usize::try_from(header(1)?).ok()?;
+    let booleans = usize::try_from(header(2)?).ok()?;
+    let numbers = usize::try_from(header(3)?).ok()?;
+    if numbers <= COLORS {
+        return Some(0);
+    }
+
+    // The numbers section always starts on an even byte
+    let mut start = 12 + names_size + booleans;
+    start += start % 2;
+    let start = start + COLORS * number_size;
+    let bytes = entry.get(start..start + number_size)?;
+    let colors = match number_size {
+        2 => i16::from_le_bytes([bytes[0], bytes[1]]) as i32,
+        _ => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    };
+
+    // Missing capabilities are negative
+    Some(colors.max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A compiled terminfo entry with the given numbers, stored in 16 bits with the original magic number or in 32 with
+    // the extended one
+    fn terminfo(number_size: usize, names: &[u8], booleans: usize, numbers: &[i32]) -> Vec<u8> {
+        let magic: i16 = if number_size == 2 { 0o432 } else { 0o1036 };
+        let header = [magic, names.len() as i16, booleans as i16, numbers.len() as i16, 0, 0];
+        let mut entry: Vec<u8> = header.iter().flat_map(|value| value.to_le_bytes()).collect();
+        entry.extend(names);
+        entry.extend(std::iter::repeat_n(1, booleans));
+        if entry.len() % 2 == 1 {
+            entry.push(0);
+        }
+        for number in numbers {
+            match number_size {
+                2 => entry.extend((*number as i16).to_le_bytes()),
+                _ => entry.extend(number.to_le_bytes()),
+            }
+        }
+        entry
+    }
+
+    fn with_colors(colors: i32) -> Vec<i32> {
+        let mut numbers = vec![-1; 15];
+        numbers[0] = 80;
+        numbers[13] = colors;
+        numbers
+    }
+
+    #[test]
+    fn terminfo_entries() {
+        let cases = [
+            (terminfo(2, b"xterm|xterm\0", 3, &with_colors(8)), Some(8)),
+            (terminfo(2, b"xterm-256color\0", 2, &with_colors(256)), Some(256)),
+            (terminfo(4, b"xterm-direct\0", 3, &with_colors(1 << 24)), Some(1 << 24)),
+            (terminfo(4, b"xterm-256color\0", 0, &with_colors(256)), Some(256)),
+            // A capability that's missing is -1, and so is every capability past the end of the numbers
+            (terminfo(2, b"vt100\0", 1, &with_colors(-1)), Some(0)),
+            (terminfo(2, b"vt100\0", 1, &[80; 13]), Some(0)),
+            // Not terminfo at all, or cut off
+            (b"\x1b[31m not terminfo".to_vec(), None),
+            (terminfo(2, b"xterm\0", 1, &with_colors(8))[..30].to_vec(), None),
+            (Vec::new(), None),
+        ];
+        for (index, (entry, colors)) in cases.iter().enumerate() {
+            assert_eq!(parse_terminfo_colors(entry), *colors, "case {}", index);
+        }
+    }
+
+    #[test]
+    fn ansi256() {
+        let cases = [
+            ((0, 0, 0), 16),
+            ((255, 255, 255), 231),
+            ((255, 0, 0), 196),
+            ((95, 135, 175), 67),
+            // Greys are closer to the grey ramp than the cube
+            ((128, 128, 128), 244),
+            ((10, 10, 10), 232),
+            ((238, 238, 238), 255),
+        ];
+        for (rgb, value) in cases {
+            assert_eq!(nearest_ansi256(rgb), value, "{:?}", rgb);
+        }
+    }
+
+    #[test]
+    fn downsample() {
+        let rgb = |r, g, b| Color::Rgb { r, g, b };
+        let cases = [
+            (ColorDepth::TrueColor, rgb(1, 2, 3), rgb(1, 2, 3)),
+            (ColorDepth::Ansi256, rgb(255, 0, 0), Color::AnsiValue(196)),
+            (ColorDepth::Ansi256, rgb(130, 130, 130), Color::AnsiValue(244)),
+            (ColorDepth::Ansi256, Color::Red, Color::Red),
+            (ColorDepth::Ansi16, rgb(250, 10, 10), Color::Red),
+            (ColorDepth::Ansi16, rgb(190, 190, 190), Color::Grey),
+            (ColorDepth::Ansi16, rgb(0, 0, 140), Color::DarkBlue),
+            (ColorDepth::Ansi16, Color::AnsiValue(4), Color::DarkBlue),
+            (ColorDepth::Ansi16, Color::AnsiValue(196), Color::Red),
+            (ColorDepth::Ansi16, Color::AnsiValue(244), Color::DarkGrey),
+            (ColorDepth::Ansi16, Color::AnsiValue(231), Color::White),
+            (ColorDepth::Ansi16, Color::Magenta, Color::Magenta),
+            (ColorDepth::NoColor, Color::Red, Color::Reset),
+            (ColorDepth::NoColor, rgb(1, 2, 3), Color::Reset),
+        ];
+        for (depth, color, downsampled) in cases {
+            assert_eq!(depth.downsample(color), downsampled, "{:?} at {:?}", color, depth);
+        }
+
+        for depth in [ColorDepth::NoColor, ColorDepth::Ansi16, ColorDepth::Ansi256, ColorDepth::TrueColor] {
+            assert_eq!(depth.downsample(Color::Reset), Color::Reset);
+        }
+    }
+}
@@ -1,57 +1,26 @@
 use bevy::prelude::*;
 use bevy::reflect::TypeUuid;
-use bevy::utils::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use std::default::Default;
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 pub use crossterm::style::Color;
 
-#[derive(Default, Resource)]
-pub(crate) struct PreviousEntityDetails(pub HashMap<Entity, (PreviousPosition, PreviousSize)>);
-
-#[derive(Resource)]
-pub(crate) struct PreviousWindowColors(pub Colors);
-
-impl Default for PreviousWindowColors {
-    fn default() -> Self {
-        PreviousWindowColors(Colors::term_colors())
-    }
-}
-
-#[derive(Default, Resource)]
-pub(crate) struct EntitiesToRedraw {
-    pub full_redraw: bool,
-    pub to_clear: HashSet<Entity>,
-    pub to_draw: Vec<EntityDepth>,
-}
-
-pub(crate) struct EntityDepth {
-    pub entity: Entity,
-    pub z: i32,
-}
-
 #[derive(Bundle, Default)]
 pub struct SpriteBundle {
     pub sprite: Handle<Sprite>,
     pub position: Position,
+    pub global_position: GlobalPosition,
     pub stylemap: Handle<StyleMap>,
     pub visible: Visible,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Colors {
     pub foreground: Option<Color>,
     pub background: Option<Color>,
 }
-impl Default for Colors {
-    fn default() -> Self {
-        Colors {
-            foreground: None,
-            background: None,
-        }
-    }
-}
 
 impl Colors {
     pub fn term_colors() -> Colors {
@@ -148,7 +117,7 @@ mod attribute_parser {
     }
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Style {
     #[serde(with = "attribute_parser")]
     pub attributes: crossterm::style::Attributes,
@@ -253,6 +222,10 @@ impl StyleMap {
     }
 
     /// If there is a style available in the map, this fetches it. Otherwise, this returns None
+    ///
+    /// The map is indexed by screen column, not by grapheme, so it lines up with the sprite as it's displayed. A wide
+    /// grapheme takes up two columns and is drawn with the style of the first one; the style of the second column is
+    /// never used.
     pub fn style_at(&self, x: usize, y: usize) -> Option<&Style> {
         self.map.get(y).and_then(|vec| vec.get(x))
     }
@@ -300,6 +273,12 @@ impl Visible {
     }
 }
 
+/// How many columns a grapheme takes up on the screen. Every grapheme takes at least one column, even the ones that
+/// don't print anything, and none take more than two
+pub fn grapheme_width(grapheme: &str) -> usize {
+    UnicodeWidthStr::width(grapheme).clamp(1, 2)
+}
+
 #[derive(Default, Eq, PartialEq, Debug, TypeUuid)]
 #[uuid = "f04f5352-e656-4a90-95a5-2269c02d0091"]
 pub struct Sprite {
@@ -310,12 +289,18 @@ pub struct Sprite {
     // must be updated in tandem
     graphemes: Vec<Vec<(usize, usize)>>,
     max_width: usize,
+    // The column and line of the sprite that's drawn at the entity's position
+    anchor: (i32, i32),
+    // A character that's never drawn, so whatever is underneath shows through
+    transparent: Option<char>,
 }
 
 impl Sprite {
     pub fn new<T: std::string::ToString>(value: T) -> Sprite {
-        let mut sprite = Sprite::default();
-        sprite.data = value.to_string();
+        let mut sprite = Sprite {
+            data: value.to_string(),
+            ..Default::default()
+        };
 
         Sprite::convert_to_sprite(&mut sprite);
 
@@ -326,30 +311,93 @@ impl Sprite {
         sprite.max_width = 0;
 
         let mut current_line = Vec::new();
+        let mut line_width = 0;
         for (start, grapheme) in UnicodeSegmentation::grapheme_indices(&*sprite.data, true) {
             if grapheme == "\r" || grapheme == "\n" || grapheme == "\r\n" {
-                sprite.max_width = std::cmp::max(sprite.max_width, current_line.len());
+                sprite.max_width = std::cmp::max(sprite.max_width, line_width);
                 sprite.graphemes.push(std::mem::take(&mut current_line));
+                line_width = 0;
                 continue;
             }
 
             current_line.push((start, start + grapheme.len()));
+            line_width += grapheme_width(grapheme);
         }
 
         if !current_line.is_empty() {
-            sprite.max_width = std::cmp::max(sprite.max_width, current_line.len());
+            sprite.max_width = std::cmp::max(sprite.max_width, line_width);
             sprite.graphemes.push(std::mem::take(&mut current_line));
         }
     }
 
+    /// Moves the sprite so the given column and line of it is drawn at the entity's position, instead of its top left
+    /// corner. The anchor can be outside of the sprite
+    pub fn with_anchor(mut self, column: i32, line: i32) -> Sprite {
+        self.anchor = (column, line);
+        self
+    }
+
+    /// Leaves a character out whenever the sprite is drawn, letting whatever is underneath show through. Unlike the
+    /// unstyled spaces of a transparent `Visible`, this works no matter how the cell is styled
+    pub fn with_transparent(mut self, transparent: char) -> Sprite {
+        self.transparent = Some(transparent);
+        self
+    }
+
+    pub fn set_anchor(&mut self, column: i32, line: i32) {
+        self.anchor = (column, line);
+    }
+
+    pub fn set_transparent(&mut self, transparent: Option<char>) {
+        self.transparent = transparent;
+    }
+
     pub fn data(&self) -> &str {
         &self.data
     }
 
+    pub fn anchor(&self) -> (i32, i32) {
+        self.anchor
+    }
+
+    pub fn transparent(&self) -> Option<char> {
+        self.transparent
+    }
+
+    /// Whether a grapheme is the sprite's transparent character
+    pub fn is_transparent_grapheme(&self, grapheme: &str) -> bool {
+        let mut chars = grapheme.chars();
+        self.transparent.is_some() && chars.next() == self.transparent && chars.next().is_none()
+    }
+
+    /// How many columns the widest line takes up on the screen. Wide graphemes, like most CJK characters and emoji,
+    /// take up two columns
     pub fn width(&self) -> usize {
         self.max_width
     }
 
+    /// How many columns a line takes up on the screen
+    pub fn line_width(&self, line: usize) -> usize {
+        self.columns(line)
+            .last()
+            .map_or(0, |(column, grapheme)| column + grapheme_width(grapheme))
+    }
+
+    /// The graphemes in a line, along with the column each one starts at
+    pub fn columns(&self, line: usize) -> impl Iterator<Item = (usize, &str)> {
+        let mut column = 0;
+        self.graphemes
+            .get(line)
+            .into_iter()
+            .flatten()
+            .map(move |grapheme| {
+                let grapheme = self.grapheme(grapheme);
+                let start = column;
+                column += grapheme_width(grapheme);
+                (start, grapheme)
+            })
+    }
+
     pub fn height(&self) -> usize {
         self.graphemes.len()
     }
@@ -377,7 +425,8 @@ impl Sprite {
     }
 }
 
-#[derive(Default, Eq, PartialEq, Debug, Component)]
+/// Where an entity is on the screen. If the entity has a parent with a position, this is relative to the parent
+#[derive(Default, Clone, Copy, Eq, PartialEq, Debug, Component)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -412,21 +461,12 @@ impl Position {
     }
 }
 
-#[derive(Default, Eq, PartialEq, Debug)]
-pub(crate) struct PreviousPosition {
-    pub x: i32,
-    pub y: i32,
-    pub z: i32,
-}
-
-#[derive(Default, Eq, PartialEq, Debug)]
-pub(crate) struct PreviousSize {
-    pub width: u16,
-    pub height: u16,
-}
-
-#[derive(Default, Eq, PartialEq, Debug)]
-pub(crate) struct GlobalPosition {
+/// Where an entity actually ends up on the screen. For an entity with a parent, its `Position` is relative to the
+/// parent's, and this is the sum of its own and all of its ancestors' positions. It's calculated by the plugin in
+/// `CoreSet::PostUpdate`, so it should be treated as read only, and is only up to date from then until the next
+/// update. Entities with a `Position` get one added if they were spawned without it.
+#[derive(Default, Clone, Copy, Eq, PartialEq, Debug, Component)]
+pub struct GlobalPosition {
     pub x: i32,
     pub y: i32,
     pub z: i32,
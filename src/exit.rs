@@ -0,0 +1,119 @@
+use std::sync::Mutex;
+
+use bevy::app::AppExit;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// A key along with the modifiers that have to be held with it
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        KeyChord { code, modifiers }
+    }
+
+    pub fn key(code: KeyCode) -> KeyChord {
+        KeyChord::new(code, KeyModifiers::NONE)
+    }
+
+    pub fn ctrl(c: char) -> KeyChord {
+        KeyChord::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    /// Whether a key press is this chord. Extra modifiers being held doesn't stop it from matching
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        event.kind != KeyEventKind::Release
+            && event.code == self.code
+            && event.modifiers.contains(self.modifiers)
+    }
+}
+
+/// What the runner does when it sees the exit chord
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitBehavior {
+    /// Exit as soon as the chord is pressed
+    Exit(KeyChord),
+    /// Send an `ExitRequested` event when the chord is pressed. The app exits at the end of that frame unless a system
+    /// sends a `CancelExit` event in response
+    Request(KeyChord),
+    /// There's no exit chord, the app has to send `AppExit` itself
+    Disabled,
+}
+
+impl Default for ExitBehavior {
+    fn default() -> Self {
+        ExitBehavior::Exit(KeyChord::ctrl('c'))
+    }
+}
+
+/// Asks the app to exit. Unless a `CancelExit` event is sent during the same frame, an `AppExit` follows
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExitRequested;
+
+/// Vetoes any `ExitRequested` events sent this frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CancelExit;
+
+/// The status the app finished with. Set it before sending `AppExit` to report a failure, or any time before then.
+///
+/// The runner never exits the process itself. Once `App::run` returns, `last_teardown` has the final code for the
+/// process to exit with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub struct ExitCode(pub i32);
+
+/// Why the runner stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// `AppExit` was sent, by a system or the exit chord
+    Exited,
+    /// The process received SIGTERM or SIGHUP
+    Terminated,
+    /// The app was only run once, because of `RunMode::Once`
+    Finished,
+}
+
+/// Sent once the runner has stopped and the terminal is back the way it was found, so it's back to the normal screen
+/// outside of inline mode. Only the `Teardown` schedule is run to handle it, which is the place to print a summary of
+/// what happened with `println!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalTeardown {
+    pub reason: ExitReason,
+    /// The `ExitCode` when the runner stopped
+    pub exit_code: i32,
+}
+
+/// The schedule run once after the terminal has been torn down, instead of another full update. Systems that handle
+/// `TerminalTeardown` go here with `.in_schedule(Teardown)`. Nothing is drawn, there's no screen to draw to
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Teardown;
+
+// How the runner last finished, with the exit code as it was after the `Teardown` schedule ran
+static LAST_TEARDOWN: Mutex<Option<TerminalTeardown>> = Mutex::new(None);
+
+/// How the runner finished, once `App::run` has returned. The exit code is the `ExitCode` after the `Teardown`
+/// schedule ran, so systems handling the teardown can still change it. `None` if the runner hasn't finished
+pub fn last_teardown() -> Option<TerminalTeardown> {
+    *LAST_TEARDOWN.lock().unwrap()
+}
+
+pub(crate) fn set_last_teardown(teardown: TerminalTeardown) {
+    *LAST_TEARDOWN.lock().unwrap() = Some(teardown);
+}
+
+pub(crate) fn exit_on_request(
+    mut requests: EventReader<ExitRequested>,
+    mut cancels: EventReader<CancelExit>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let requested = requests.iter().count() > 0;
+    let cancelled = cancels.iter().count() > 0;
+
+    if requested && !cancelled {
+        app_exit.send(AppExit);
+    }
+}
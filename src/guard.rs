@@ -0,0 +1,162 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once};
+
+use crossterm::ExecutableCommand;
+
+// Whether some guard is responsible for the terminal right now. The panic hook is process wide, so this has to be too
+static ARMED: AtomicBool = AtomicBool::new(false);
+// Whether the terminal is drawn to inline, in which case there's no alternate screen to leave
+static INLINE: AtomicBool = AtomicBool::new(false);
+// Whether the terminal was asked to report key releases, which has to be undone or the shell gets escape codes as input
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+static INSTALL_PANIC_HOOK: Once = Once::new();
+// Whether signals the runner has stopped handling should do what they'd do by default again
+#[cfg(unix)]
+static DEFAULT_ACTIONS: AtomicBool = AtomicBool::new(false);
+// The signals that have been given a handler that runs their default action while `DEFAULT_ACTIONS` is set
+#[cfg(unix)]
+static DEFAULT_HANDLERS: std::sync::Mutex<Vec<i32>> = std::sync::Mutex::new(Vec::new());
+
+/// Puts the process' terminal back the way it was found: raw mode, mouse capture, bracketed paste and focus reporting
+/// off, the alternate screen left and the cursor shown. This happens when the guard is dropped, so early returns are
+/// covered, and a panic hook restores the terminal before the panic message is printed so the message is actually
+/// readable.
+///
+/// The runner holds one of these whenever it's drawing to a real terminal. It's only needed directly by custom runners.
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl Default for TerminalGuard {
+    fn default() -> Self {
+        TerminalGuard::new()
+    }
+}
+
+impl TerminalGuard {
+    pub fn new() -> TerminalGuard {
+        TerminalGuard::arm(false)
+    }
+
+    /// A guard for an app rendering inline. The screen is left as it is, and output carries on from a fresh line
+    pub fn inline() -> TerminalGuard {
+        TerminalGuard::arm(true)
+    }
+
+    fn arm(inline: bool) -> TerminalGuard {
+        INSTALL_PANIC_HOOK.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                restore();
+                previous(info);
+            }));
+        });
+
+        INLINE.store(inline, Ordering::SeqCst);
+        ARMED.store(true, Ordering::SeqCst);
+        TerminalGuard { _private: () }
+    }
+
+    /// The terminal has already been restored some other way, so there's nothing left for the guard to do
+    pub fn disarm(self) {
+        ARMED.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Restores the terminal if a guard is armed. Errors are ignored, this is best effort and often happens mid panic
+fn restore() {
+    if !ARMED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+
+    let mut stdout = std::io::stdout();
+    if KEYBOARD_ENHANCED.swap(false, Ordering::SeqCst) {
+        let _ = stdout.execute(crossterm::event::PopKeyboardEnhancementFlags);
+    }
+    let _ = stdout.execute(crossterm::event::DisableMouseCapture);
+    let _ = stdout.execute(crossterm::event::DisableBracketedPaste);
+    let _ = stdout.execute(crossterm::event::DisableFocusChange);
+    let _ = crossterm::terminal::disable_raw_mode();
+    if INLINE.load(Ordering::SeqCst) {
+        let _ = stdout.execute(crossterm::style::SetAttribute(crossterm::style::Attribute::Reset));
+        let _ = stdout.write_all(b"\r\n");
+    } else {
+        let _ = stdout.execute(crossterm::terminal::LeaveAlternateScreen);
+    }
+    let _ = stdout.execute(crossterm::cursor::Show);
+    let _ = stdout.flush();
+}
+
+/// Lets the guard know whether keyboard enhancement flags are pushed, so it can pop them when restoring the terminal
+pub(crate) fn set_keyboard_enhanced(enhanced: bool) {
+    KEYBOARD_ENHANCED.store(enhanced, Ordering::SeqCst);
+}
+
+/// The signal handlers the runner registered, so they can be removed again once it's finished with the terminal
+#[derive(Default)]
+pub(crate) struct SignalHandlers {
+    #[cfg(unix)]
+    handlers: Vec<(i32, signal_hook::SigId)>,
+}
+
+impl SignalHandlers {
+    #[cfg(unix)]
+    pub(crate) fn add(&mut self, signal: i32, handler: std::io::Result<signal_hook::SigId>) {
+        let handler = handler.expect("Could not register signal handler");
+        DEFAULT_ACTIONS.store(false, Ordering::SeqCst);
+        self.handlers.push((signal, handler));
+    }
+
+    /// Removes the handlers. Removing a signal's last handler would leave it doing nothing at all, so the signals go
+    /// back to what they'd do by default, like terminating the process
+    #[cfg(unix)]
+    pub(crate) fn unregister(self) {
+        let mut defaults = DEFAULT_HANDLERS.lock().unwrap();
+        for (signal, handler) in self.handlers {
+            signal_hook::low_level::unregister(handler);
+            if !defaults.contains(&signal) {
+                // Safe because it only does what signal_hook::flag::register_conditional_default does
+                let handler = unsafe {
+                    signal_hook::low_level::register(signal, move || {
+                        if DEFAULT_ACTIONS.load(Ordering::SeqCst) {
+                            let _ = signal_hook::low_level::emulate_default_handler(signal);
+                        }
+                    })
+                };
+                handler.expect("Could not register signal handler");
+                defaults.push(signal);
+            }
+        }
+        DEFAULT_ACTIONS.store(true, Ordering::SeqCst);
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn unregister(self) {}
+}
+
+/// A flag that gets set when the process receives SIGTERM or SIGHUP, so the runner can shut down cleanly instead of
+/// being killed with the terminal still in raw mode. A second signal while shutting down exits immediately.
+#[cfg(unix)]
+pub(crate) fn termination_flag(handlers: &mut SignalHandlers) -> Arc<AtomicBool> {
+    use signal_hook::consts::{SIGHUP, SIGTERM};
+
+    let flag = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGHUP] {
+        // Registered first so it sees the flag before this signal sets it
+        handlers.add(signal, signal_hook::flag::register_conditional_shutdown(signal, 1, flag.clone()));
+        handlers.add(signal, signal_hook::flag::register(signal, flag.clone()));
+    }
+    flag
+}
+
+#[cfg(not(unix))]
+pub(crate) fn termination_flag(_handlers: &mut SignalHandlers) -> Arc<AtomicBool> {
+    Arc::new(AtomicBool::new(false))
+}
@@ -0,0 +1,132 @@
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+use crate::capabilities::ColorDepth;
+use crate::components::{Color, Colors, Sprite, Style, StyleMap};
+use crate::shapes::EMPTY;
+
+/// How an image is turned into a sprite
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct ImageOptions {
+    /// How many columns and lines the sprite should be. If either is 0 it's worked out from the other so the image
+    /// keeps its shape, and without a size every pixel is half a cell
+    pub size: Option<(u32, u32)>,
+    /// The colors the image is cut down to
+    pub color_depth: ColorDepth,
+    /// Whether to spread out the difference between a pixel and the closest color to it over the pixels around it,
+    /// which looks much better than flat bands when there are only 16 or 256 colors
+    pub dither: bool,
+    /// Pixels less opaque than this are see-through. 0 means every pixel is drawn
+    pub alpha_threshold: u8,
+}
+
+impl ImageOptions {
+    pub fn new(color_depth: ColorDepth) -> ImageOptions {
+        ImageOptions {
+            size: None,
+            color_depth,
+            dither: true,
+            alpha_threshold: 128,
+        }
+    }
+}
+
+/// Draws an image with `▀` characters, two pixels to a cell, using the foreground color for the top pixel and the
+/// background color for the bottom one. Cells where both pixels are see-through aren't drawn at all, so whatever is
+/// underneath shows through
+pub(crate) fn convert(image: DynamicImage, options: &ImageOptions) -> (Sprite, StyleMap) {
+    let image = match options.size {
+        None | Some((0, 0)) => image,
+        Some((columns, lines)) => {
+            // A cell is about twice as high as it is wide, and holds two pixels stacked on top of each other
+            let aspect = image.width() as f32 / image.height().max(1) as f32;
+            let (width, height) = match (columns, lines) {
+                (0, lines) => (((lines * 2) as f32 * aspect).round().max(1.0) as u32, lines * 2),
+                (columns, 0) => (columns, (columns as f32 / aspect).round().max(1.0) as u32),
+                (columns, lines) => (columns, lines * 2),
+            };
+            image.resize_exact(width, height, FilterType::Triangle)
+        }
+    };
+
+    let image = image.to_rgba8();
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let opaque: Vec<bool> = image
+        .pixels()
+        .map(|pixel| options.alpha_threshold == 0 || pixel[3] >= options.alpha_threshold)
+        .collect();
+    let colors = quantize(&image, &opaque, options);
+
+    let mut data = String::new();
+    let mut map = Vec::new();
+    for line in 0..height.div_ceil(2) {
+        if line > 0 {
+            data.push('\n');
+        }
+
+        let mut styles = Vec::new();
+        for column in 0..width {
+            let pixel = |y: usize| {
+                let index = y * width + column;
+                (y < height && opaque[index]).then(|| colors[index])
+            };
+            let (character, foreground, background) = match (pixel(line * 2), pixel(line * 2 + 1)) {
+                (None, None) => (EMPTY, None, None),
+                (Some(top), None) => ('▀', Some(top), None),
+                (None, Some(bottom)) => ('▄', Some(bottom), None),
+                (Some(top), Some(bottom)) => ('▀', Some(top), Some(bottom)),
+            };
+            data.push(character);
+            styles.push(Style::with_colors(Colors {
+                foreground,
+                background,
+            }));
+        }
+        map.push(styles);
+    }
+
+    (Sprite::new(data).with_transparent(EMPTY), StyleMap::new(Style::default(), map))
+}
+
+/// The color every pixel is drawn in, with Floyd-Steinberg dithering if it's turned on and the colors are limited
+fn quantize(image: &image::RgbaImage, opaque: &[bool], options: &ImageOptions) -> Vec<Color> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let dither = options.dither && matches!(options.color_depth, ColorDepth::Ansi16 | ColorDepth::Ansi256);
+
+    let mut pixels: Vec<[f32; 3]> = image
+        .pixels()
+        .map(|pixel| [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32])
+        .collect();
+    let mut colors = Vec::with_capacity(pixels.len());
+    for index in 0..pixels.len() {
+        let [r, g, b] = pixels[index].map(|channel| channel.round().clamp(0.0, 255.0) as u8);
+        let (color, shown) = options.color_depth.quantize((r, g, b));
+        colors.push(color);
+
+        // See-through pixels neither give nor take any error, since they aren't seen
+        if !dither || !opaque[index] {
+            continue;
+        }
+
+        let error = [
+            pixels[index][0] - shown.0 as f32,
+            pixels[index][1] - shown.1 as f32,
+            pixels[index][2] - shown.2 as f32,
+        ];
+        let (x, y) = (index % width, index / width);
+        let neighbours: [(i32, usize, f32); 4] = [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)];
+        for (dx, dy, weight) in neighbours {
+            let (nx, ny) = (x as i32 + dx, y + dy);
+            if nx < 0 || nx as usize >= width || ny >= height {
+                continue;
+            }
+            let neighbour = ny * width + nx as usize;
+            if opaque[neighbour] {
+                for channel in 0..3 {
+                    pixels[neighbour][channel] += error[channel] * weight / 16.0;
+                }
+            }
+        }
+    }
+    colors
+}
@@ -0,0 +1,590 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bevy::prelude::Resource;
+use crossterm::style::{Attribute, Attributes, Color};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::backend::TerminalBackend;
+use crate::components::grapheme_width;
+
+/// A single character cell of the virtual screen, as a real terminal would display it. The cell to the right of a
+/// wide grapheme is covered by it, and has an empty grapheme
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub grapheme: String,
+    pub foreground: Color,
+    pub background: Color,
+    pub attributes: Attributes,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            grapheme: " ".to_string(),
+            foreground: Color::Reset,
+            background: Color::Reset,
+            attributes: Attributes::default(),
+        }
+    }
+}
+
+/// A terminal backend that interprets the escape sequences the plugin emits into an in-memory grid of cells, so what
+/// was rendered can be inspected without a tty. Clones share the same screen.
+#[derive(Clone, Resource)]
+pub struct HeadlessBackend(Arc<Mutex<Screen>>);
+
+impl HeadlessBackend {
+    pub fn new(width: u16, height: u16) -> HeadlessBackend {
+        HeadlessBackend(Arc::new(Mutex::new(Screen::new(width, height))))
+    }
+
+    fn screen(&self) -> MutexGuard<'_, Screen> {
+        self.0.lock().unwrap()
+    }
+
+    pub fn width(&self) -> u16 {
+        self.screen().width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.screen().height
+    }
+
+    /// The cell at column x, row y, or None if that's off the screen
+    pub fn cell(&self, x: u16, y: u16) -> Option<Cell> {
+        let screen = self.screen();
+        screen.index(x, y).map(|idx| screen.cells[idx].clone())
+    }
+
+    /// The text of row y with styling stripped, or None if that's off the screen
+    pub fn line(&self, y: u16) -> Option<String> {
+        let screen = self.screen();
+        if y >= screen.height {
+            return None;
+        }
+        Some(screen.line(y))
+    }
+
+    /// The text of every row, top to bottom
+    pub fn lines(&self) -> Vec<String> {
+        let screen = self.screen();
+        (0..screen.height).map(|y| screen.line(y)).collect()
+    }
+
+    /// Where the cursor currently is in (column, row)
+    pub fn cursor(&self) -> (u16, u16) {
+        let screen = self.screen();
+        (
+            std::cmp::min(screen.cursor_x, screen.width.saturating_sub(1)),
+            screen.cursor_y,
+        )
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.screen().cursor_visible
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.screen().title.clone()
+    }
+
+    pub fn is_raw_mode(&self) -> bool {
+        self.screen().raw_mode
+    }
+
+    pub fn is_alternate_screen(&self) -> bool {
+        self.screen().alternate_screen
+    }
+
+    pub fn is_mouse_captured(&self) -> bool {
+        self.screen().mouse_captured
+    }
+
+    /// The cells that have been printed to or erased since this was last called, as (column, row), in the order it
+    /// happened. Handy for checking that only what changed is drawn again
+    pub fn take_written(&self) -> Vec<(u16, u16)> {
+        std::mem::take(&mut self.screen().written)
+    }
+}
+
+impl Write for HeadlessBackend {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.screen().feed(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl TerminalBackend for HeadlessBackend {
+    fn size(&self) -> std::io::Result<(u16, u16)> {
+        let screen = self.screen();
+        Ok((screen.width, screen.height))
+    }
+
+    fn cursor_position(&mut self) -> std::io::Result<(u16, u16)> {
+        Ok(self.cursor())
+    }
+
+    fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+        self.screen().raw_mode = true;
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+        self.screen().raw_mode = false;
+        Ok(())
+    }
+}
+
+struct Screen {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    // The cursor may sit one past the last column, in which case the next print wraps like a real terminal would
+    cursor_x: u16,
+    cursor_y: u16,
+    saved_cursor: (u16, u16),
+    cursor_visible: bool,
+    // The colors and attributes the next printed grapheme will have
+    pen: Cell,
+    title: Option<String>,
+    raw_mode: bool,
+    alternate_screen: bool,
+    mouse_captured: bool,
+    // Bytes of an escape sequence or utf-8 character that was split between writes
+    pending: Vec<u8>,
+    // Cells written to since `take_written` was last called
+    written: Vec<(u16, u16)>,
+}
+
+impl Screen {
+    fn new(width: u16, height: u16) -> Screen {
+        Screen {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+            cursor_x: 0,
+            cursor_y: 0,
+            saved_cursor: (0, 0),
+            cursor_visible: true,
+            pen: Cell::default(),
+            title: None,
+            raw_mode: false,
+            alternate_screen: false,
+            mouse_captured: false,
+            pending: Vec::new(),
+            written: Vec::new(),
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    fn line(&self, y: u16) -> String {
+        let start = y as usize * self.width as usize;
+        self.cells[start..start + self.width as usize]
+            .iter()
+            .map(|cell| cell.grapheme.as_str())
+            .collect()
+    }
+
+    fn blank(&self) -> Cell {
+        // Erasing fills with the current background, like xterm does
+        Cell {
+            background: self.pen.background,
+            ..Default::default()
+        }
+    }
+
+    fn erase(&mut self, from: usize, to: usize) {
+        let blank = self.blank();
+        let to = std::cmp::min(to, self.cells.len());
+        for cell in &mut self.cells[from..to] {
+            *cell = blank.clone();
+        }
+        let width = self.width as usize;
+        self.written
+            .extend((from..to).map(|idx| ((idx % width) as u16, (idx / width) as u16)));
+    }
+
+    fn scroll_up(&mut self, lines: u16) {
+        let lines = std::cmp::min(lines, self.height) as usize * self.width as usize;
+        self.cells.drain(0..lines);
+        let blank = self.blank();
+        self.cells.resize(self.width as usize * self.height as usize, blank);
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor_y + 1 >= self.height {
+            self.scroll_up(1);
+        } else {
+            self.cursor_y += 1;
+        }
+    }
+
+    fn print(&mut self, grapheme: &str) {
+        let width = grapheme_width(grapheme) as u16;
+        if self.cursor_x + width > self.width && self.cursor_x > 0 {
+            self.cursor_x = 0;
+            self.line_feed();
+        }
+
+        if let Some(idx) = self.index(self.cursor_x, self.cursor_y) {
+            self.written.push((self.cursor_x, self.cursor_y));
+            self.split_wide(idx);
+            self.cells[idx] = Cell {
+                grapheme: grapheme.to_string(),
+                ..self.pen.clone()
+            };
+
+            // Wide graphemes also cover the next cell, which is left empty
+            if width == 2 && self.cursor_x + 1 < self.width {
+                self.split_wide(idx + 1);
+                self.cells[idx + 1] = Cell {
+                    grapheme: String::new(),
+                    ..self.pen.clone()
+                };
+            }
+        }
+        self.cursor_x += width;
+    }
+
+    /// Overwriting half of a wide grapheme blanks out the other half, like a terminal would
+    fn split_wide(&mut self, idx: usize) {
+        let width = self.width as usize;
+        if self.cells[idx].grapheme.is_empty() && !idx.is_multiple_of(width) {
+            self.cells[idx - 1].grapheme = " ".to_string();
+        } else if !(idx + 1).is_multiple_of(width) && self.cells[idx + 1].grapheme.is_empty() {
+            self.cells[idx + 1].grapheme = " ".to_string();
+        }
+    }
+
+    fn move_to(&mut self, x: u16, y: u16) {
+        self.cursor_x = std::cmp::min(x, self.width.saturating_sub(1));
+        self.cursor_y = std::cmp::min(y, self.height.saturating_sub(1));
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let pending = std::mem::take(&mut self.pending);
+
+        let mut pos = 0;
+        while pos < pending.len() {
+            let consumed = match pending[pos] {
+                0x1b => self.escape(&pending[pos..]),
+                b'\r' => {
+                    self.cursor_x = 0;
+                    Some(1)
+                }
+                b'\n' => {
+                    self.line_feed();
+                    Some(1)
+                }
+                0x08 => {
+                    self.cursor_x = self.cursor_x.saturating_sub(1);
+                    Some(1)
+                }
+                b'\t' => {
+                    let next_stop = (self.cursor_x / 8 + 1) * 8;
+                    self.cursor_x = std::cmp::min(next_stop, self.width.saturating_sub(1));
+                    Some(1)
+                }
+                byte if byte < 0x20 || byte == 0x7f => Some(1),
+                _ => self.text(&pending[pos..]),
+            };
+
+            match consumed {
+                Some(consumed) => pos += consumed,
+                None => {
+                    // The rest of this sequence hasn't been written yet
+                    self.pending = pending[pos..].to_vec();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Prints the run of text at the start of bytes, returning how many bytes were used
+    fn text(&mut self, bytes: &[u8]) -> Option<usize> {
+        let end = bytes
+            .iter()
+            .position(|byte| *byte < 0x20 || *byte == 0x7f)
+            .unwrap_or(bytes.len());
+
+        let text = match std::str::from_utf8(&bytes[..end]) {
+            Ok(text) => text,
+            Err(err) if err.error_len().is_none() && err.valid_up_to() == 0 => return None,
+            Err(err) if err.error_len().is_none() => {
+                std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap()
+            }
+            Err(err) => {
+                // Not utf-8 at all, print a replacement character and move on
+                let valid = std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap();
+                for grapheme in valid.graphemes(true) {
+                    self.print(grapheme);
+                }
+                self.print("\u{fffd}");
+                return Some(err.valid_up_to() + err.error_len().unwrap());
+            }
+        };
+
+        for grapheme in text.graphemes(true) {
+            self.print(grapheme);
+        }
+        Some(text.len())
+    }
+
+    /// Interprets the escape sequence at the start of bytes, returning how many bytes it took up
+    fn escape(&mut self, bytes: &[u8]) -> Option<usize> {
+        match bytes.get(1)? {
+            b'[' => {
+                // Control sequence: parameters, intermediates, then a final byte in 0x40..=0x7e
+                let len = bytes[2..].iter().position(|byte| (0x40..=0x7e).contains(byte))?;
+                let params = std::str::from_utf8(&bytes[2..2 + len]).unwrap_or("");
+                self.csi(params, bytes[2 + len]);
+                Some(len + 3)
+            }
+            b']' => {
+                // Operating system command, terminated by BEL or ST
+                let mut end = 2;
+                loop {
+                    match bytes.get(end)? {
+                        0x07 => {
+                            self.osc(&bytes[2..end]);
+                            return Some(end + 1);
+                        }
+                        0x1b if bytes.get(end + 1)? == &b'\\' => {
+                            self.osc(&bytes[2..end]);
+                            return Some(end + 2);
+                        }
+                        _ => end += 1,
+                    }
+                }
+            }
+            b'7' => {
+                self.saved_cursor = (self.cursor_x, self.cursor_y);
+                Some(2)
+            }
+            b'8' => {
+                let (x, y) = self.saved_cursor;
+                self.move_to(x, y);
+                Some(2)
+            }
+            _ => Some(2),
+        }
+    }
+
+    fn osc(&mut self, bytes: &[u8]) {
+        let command = String::from_utf8_lossy(bytes);
+        if let Some((kind, title)) = command.split_once(';') {
+            if kind == "0" || kind == "2" {
+                self.title = Some(title.to_string());
+            }
+        }
+    }
+
+    fn csi(&mut self, params: &str, action: u8) {
+        if let Some(modes) = params.strip_prefix('?') {
+            for mode in modes.split(';') {
+                self.private_mode(mode, action == b'h');
+            }
+            return;
+        }
+        if params.starts_with(['<', '=', '>']) {
+            // Keyboard protocol and other extensions that don't change what's on the screen
+            return;
+        }
+
+        let args: Vec<u16> = params
+            .split(';')
+            .map(|arg| arg.parse().unwrap_or(0))
+            .collect();
+        let arg = |idx: usize, default: u16| match args.get(idx) {
+            Some(0) | None => default,
+            Some(value) => *value,
+        };
+
+        match action {
+            b'H' | b'f' => self.move_to(arg(1, 1) - 1, arg(0, 1) - 1),
+            b'A' => self.cursor_y = self.cursor_y.saturating_sub(arg(0, 1)),
+            b'B' => self.move_to(self.cursor_x, self.cursor_y.saturating_add(arg(0, 1))),
+            b'C' => self.cursor_x = std::cmp::min(self.cursor_x.saturating_add(arg(0, 1)), self.width),
+            b'D' => self.cursor_x = std::cmp::min(self.cursor_x, self.width).saturating_sub(arg(0, 1)),
+            b'E' => self.move_to(0, self.cursor_y.saturating_add(arg(0, 1))),
+            b'F' => self.move_to(0, self.cursor_y.saturating_sub(arg(0, 1))),
+            b'G' => self.move_to(arg(0, 1) - 1, self.cursor_y),
+            b'd' => self.move_to(self.cursor_x, arg(0, 1) - 1),
+            b'J' => {
+                let cursor = self.index(std::cmp::min(self.cursor_x, self.width.saturating_sub(1)), self.cursor_y).unwrap_or(0);
+                match args[0] {
+                    0 => self.erase(cursor, self.cells.len()),
+                    1 => self.erase(0, cursor + 1),
+                    _ => self.erase(0, self.cells.len()),
+                }
+            }
+            b'K' => {
+                let start = self.cursor_y as usize * self.width as usize;
+                let cursor = start + std::cmp::min(self.cursor_x, self.width) as usize;
+                let end = start + self.width as usize;
+                match args[0] {
+                    0 => self.erase(cursor, end),
+                    1 => self.erase(start, std::cmp::min(cursor + 1, end)),
+                    _ => self.erase(start, end),
+                }
+            }
+            b'S' => self.scroll_up(arg(0, 1)),
+            b's' => self.saved_cursor = (self.cursor_x, self.cursor_y),
+            b'u' => {
+                let (x, y) = self.saved_cursor;
+                self.move_to(x, y);
+            }
+            b'm' => apply_sgr(&mut self.pen, params),
+            _ => {}
+        }
+    }
+
+    fn private_mode(&mut self, mode: &str, enabled: bool) {
+        match mode {
+            "25" => self.cursor_visible = enabled,
+            "1049" => self.alternate_screen = enabled,
+            "1000" => self.mouse_captured = enabled,
+            _ => {}
+        }
+    }
+}
+
+/// Applies the parameters of an SGR escape sequence (`ESC [ ... m`) to the pen that graphemes are printed with
+pub(crate) fn apply_sgr(pen: &mut Cell, params: &str) {
+    let mut groups = params.split(';');
+    while let Some(group) = groups.next() {
+        let mut sub = group.split(':');
+        let code: u16 = sub.next().and_then(|code| code.parse().ok()).unwrap_or(0);
+        let attributes = &mut pen.attributes;
+        match code {
+            0 => *pen = Cell::default(),
+            1 => attributes.set(Attribute::Bold),
+            2 => attributes.set(Attribute::Dim),
+            3 => attributes.set(Attribute::Italic),
+            4 => {
+                unset_underlines(attributes);
+                match sub.next() {
+                    Some("0") => {}
+                    Some("2") => attributes.set(Attribute::DoubleUnderlined),
+                    Some("3") => attributes.set(Attribute::Undercurled),
+                    Some("4") => attributes.set(Attribute::Underdotted),
+                    Some("5") => attributes.set(Attribute::Underdashed),
+                    _ => attributes.set(Attribute::Underlined),
+                }
+            }
+            5 => attributes.set(Attribute::SlowBlink),
+            6 => attributes.set(Attribute::RapidBlink),
+            7 => attributes.set(Attribute::Reverse),
+            8 => attributes.set(Attribute::Hidden),
+            9 => attributes.set(Attribute::CrossedOut),
+            20 => attributes.set(Attribute::Fraktur),
+            21 => attributes.unset(Attribute::Bold),
+            22 => {
+                attributes.unset(Attribute::Bold);
+                attributes.unset(Attribute::Dim);
+            }
+            23 => {
+                attributes.unset(Attribute::Italic);
+                attributes.unset(Attribute::Fraktur);
+            }
+            24 => unset_underlines(attributes),
+            25 => {
+                attributes.unset(Attribute::SlowBlink);
+                attributes.unset(Attribute::RapidBlink);
+            }
+            27 => attributes.unset(Attribute::Reverse),
+            28 => attributes.unset(Attribute::Hidden),
+            29 => attributes.unset(Attribute::CrossedOut),
+            51 => attributes.set(Attribute::Framed),
+            52 => attributes.set(Attribute::Encircled),
+            53 => attributes.set(Attribute::OverLined),
+            54 => {
+                attributes.unset(Attribute::Framed);
+                attributes.unset(Attribute::Encircled);
+            }
+            55 => attributes.unset(Attribute::OverLined),
+            30..=37 => pen.foreground = ansi_color((code - 30) as u8),
+            90..=97 => pen.foreground = ansi_color((code - 90 + 8) as u8),
+            40..=47 => pen.background = ansi_color((code - 40) as u8),
+            100..=107 => pen.background = ansi_color((code - 100 + 8) as u8),
+            39 => pen.foreground = Color::Reset,
+            49 => pen.background = Color::Reset,
+            38 | 48 | 58 => {
+                // Extended colors are either colon separated in this group, or take up the next few groups
+                let mut sub: Vec<u8> = sub.filter_map(|value| value.parse().ok()).collect();
+                if sub.is_empty() {
+                    match groups.next() {
+                        Some("5") => sub = vec![5, groups.next().and_then(|v| v.parse().ok()).unwrap_or(0)],
+                        Some("2") => {
+                            sub = vec![2];
+                            for _ in 0..3 {
+                                sub.push(groups.next().and_then(|v| v.parse().ok()).unwrap_or(0));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                let color = match sub.as_slice() {
+                    [5, value] => ansi_color(*value),
+                    [2, r, g, b] | [2, _, r, g, b] => Color::Rgb { r: *r, g: *g, b: *b },
+                    _ => continue,
+                };
+                match code {
+                    38 => pen.foreground = color,
+                    48 => pen.background = color,
+                    // Underline colors aren't tracked
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn unset_underlines(attributes: &mut Attributes) {
+    for underline in [
+        Attribute::Underlined,
+        Attribute::DoubleUnderlined,
+        Attribute::Undercurled,
+        Attribute::Underdotted,
+        Attribute::Underdashed,
+    ] {
+        attributes.unset(underline);
+    }
+}
+
+/// Maps an ansi palette index back to the crossterm color that produces it
+pub(crate) fn ansi_color(value: u8) -> Color {
+    match value {
+        0 => Color::Black,
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        7 => Color::Grey,
+        8 => Color::DarkGrey,
+        9 => Color::Red,
+        10 => Color::Green,
+        11 => Color::Yellow,
+        12 => Color::Blue,
+        13 => Color::Magenta,
+        14 => Color::Cyan,
+        15 => Color::White,
+        value => Color::AnsiValue(value),
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::input::Input;
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use crate::suspend::Suspended;
+
+/// What's needed to work out which keys are held from the key events the terminal sends
+#[derive(Debug, Resource)]
+pub(crate) struct KeyboardState {
+    /// How long a key stays held after the last press or repeat, on terminals that don't report releases
+    pub release_timeout: Duration,
+    /// Whether the terminal reports key releases. Set when keyboard enhancement is turned on, or as soon as a release
+    /// turns up, since that's proof enough
+    pub reports_releases: bool,
+    // When each held key was last pressed or repeated
+    last_seen: HashMap<KeyCode, Duration>,
+}
+
+impl KeyboardState {
+    pub fn new(release_timeout: Duration) -> KeyboardState {
+        KeyboardState {
+            release_timeout,
+            reports_releases: false,
+            last_seen: HashMap::new(),
+        }
+    }
+}
+
+/// Keeps `Input<KeyCode>` up to date with the key events sent since the last frame.
+///
+/// Most terminals only report key presses, and send more of them while a key is held down, after the keyboard's repeat
+/// delay. On those a key is released once it hasn't been pressed for the release timeout, which has to be longer than
+/// the repeat delay or held keys flicker.
+pub(crate) fn update_keyboard(
+    time: Res<Time>,
+    mut keys: EventReader<KeyEvent>,
+    mut suspended: EventReader<Suspended>,
+    mut focus: EventReader<WindowFocused>,
+    mut input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<KeyboardState>,
+) {
+    input.clear();
+    let now = time.elapsed();
+
+    // Keys let go of while the app was suspended or the terminal didn't have focus are never reported, so nothing is
+    // held once it's back
+    let suspended = suspended.iter().count() > 0;
+    let unfocused = focus.iter().any(|event| !event.focused);
+    if suspended || unfocused {
+        input.release_all();
+        state.last_seen.clear();
+    }
+
+    for key in keys.iter() {
+        match key.kind {
+            KeyEventKind::Press | KeyEventKind::Repeat => {
+                input.press(key.code);
+                state.last_seen.insert(key.code, now);
+            }
+            KeyEventKind::Release => {
+                input.release(key.code);
+                state.last_seen.remove(&key.code);
+                state.reports_releases = true;
+            }
+        }
+    }
+
+    if !state.reports_releases {
+        let timeout = state.release_timeout;
+        state.last_seen.retain(|code, seen| {
+            let held = now.saturating_sub(*seen) < timeout;
+            if !held {
+                input.release(*code);
+            }
+            held
+        });
+    }
+}
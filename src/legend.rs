@@ -0,0 +1,272 @@
+use anyhow::{anyhow, bail};
+use crossterm::style::{Attribute, Attributes, Color};
+
+use crate::components::{Colors, Style, StyleMap};
+
+/// Parses the hand editable stylemap format. Instead of a style per cell, a legend gives each style a character, and
+/// the map is drawn with those characters so it lines up with the sprite it's for, one character per screen column:
+///
+/// ```text
+/// # The style for anything the map doesn't cover
+/// [style]
+/// fg white
+///
+/// [legend]
+/// R: fg red, bg #202020, bold
+/// b: fg dark_blue, underlined
+///
+/// [map]
+/// RRRR bbb
+///  RR   b
+/// ```
+///
+/// A style is a comma separated list of `fg <color>`, `bg <color>` and attribute names. Colors are crossterm's color
+/// names (`red`, `dark_grey`, `reset`...), an ansi palette index, or `#rrggbb`. Spaces in the map use the sprite's
+/// style. Lines starting with `#` are comments, except in the map where every line counts, and blank lines at the end
+/// of the map are ignored.
+pub(crate) fn parse_stylemap(text: &str) -> anyhow::Result<StyleMap> {
+    stylemap_from_sections(&sections(text, &["style", "legend", "map"], &["map"])?)
+}
+
+/// A section of a file, and its lines along with their line numbers
+pub(crate) type Section<'a> = (&'a str, Vec<(usize, &'a str)>);
+
+/// Splits a file up at lines that are one of the section names in brackets, like `[map]`. Lines before the first
+/// section are in a section with an empty name. Any other line in brackets is an error, unless it's in one of the
+/// `verbatim` sections, where every line is content
+pub(crate) fn sections<'a>(text: &'a str, names: &[&'a str], verbatim: &[&str]) -> anyhow::Result<Vec<Section<'a>>> {
+    let mut sections = vec![("", Vec::new())];
+    for (number, line) in text.lines().enumerate() {
+        let header = line
+            .trim()
+            .strip_prefix('[')
+            .and_then(|name| name.strip_suffix(']'));
+        let current = sections.last_mut().unwrap();
+        match header {
+            Some(name) => match names.iter().find(|known| **known == name) {
+                Some(name) => sections.push((name, Vec::new())),
+                None if verbatim.contains(&current.0) => current.1.push((number + 1, line)),
+                None => bail!("line {}: unknown section [{}]", number + 1, name),
+            },
+            None => current.1.push((number + 1, line)),
+        }
+    }
+    Ok(sections)
+}
+
+/// The lines of a section that aren't blank or comments, trimmed
+pub(crate) fn settings<'a>(lines: &'a [(usize, &'a str)]) -> impl Iterator<Item = (usize, &'a str)> {
+    lines
+        .iter()
+        .map(|(number, line)| (*number, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Builds a stylemap from the `[style]`, `[legend]` and `[map]` sections. Any other named sections are left alone, so
+/// other formats can embed a stylemap
+pub(crate) fn stylemap_from_sections(sections: &[Section]) -> anyhow::Result<StyleMap> {
+    let mut style = Style::default();
+    let mut legend = Vec::new();
+    let mut map_lines: &[(usize, &str)] = &[];
+    for (name, lines) in sections {
+        match *name {
+            "" => {
+                if let Some((number, _)) = settings(lines).next() {
+                    bail!("line {}: expected a section like [legend] first", number);
+                }
+            }
+            "style" => {
+                for (number, line) in settings(lines) {
+                    style = parse_style(line).map_err(|err| anyhow!("line {}: {}", number, err))?;
+                }
+            }
+            "legend" => {
+                for (number, line) in settings(lines) {
+                    legend.push(parse_legend_entry(line).map_err(|err| anyhow!("line {}: {}", number, err))?);
+                }
+            }
+            "map" => map_lines = lines,
+            _ => {}
+        }
+    }
+
+    Ok(StyleMap::new(style, map_from_legend(&legend, style, map_lines)?))
+}
+
+/// A line of the legend, which is a character, a colon, and the style that character stands for
+pub(crate) fn parse_legend_entry(line: &str) -> anyhow::Result<(char, Style)> {
+    let mut chars = line.chars();
+    let key = chars.next().ok_or_else(|| anyhow!("empty legend entry"))?;
+    let spec = chars
+        .as_str()
+        .strip_prefix(':')
+        .ok_or_else(|| anyhow!("expected a ':' after the '{}' in the legend", key))?;
+    Ok((key, parse_style(spec)?))
+}
+
+/// Looks up the style of every character in the map lines. Spaces are given the default style, and trailing spaces
+/// are left out so they stay unstyled
+pub(crate) fn map_from_legend(
+    legend: &[(char, Style)],
+    default: Style,
+    lines: &[(usize, &str)],
+) -> anyhow::Result<Vec<Vec<Style>>> {
+    let lines = match lines.iter().rposition(|(_, line)| !line.trim().is_empty()) {
+        Some(last) => &lines[..=last],
+        None => &[],
+    };
+    lines
+        .iter()
+        .map(|(number, line)| {
+            line.trim_end()
+                .chars()
+                .map(|key| match legend.iter().find(|(entry, _)| *entry == key) {
+                    Some((_, style)) => Ok(*style),
+                    None if key == ' ' => Ok(default),
+                    None => bail!("line {}: '{}' isn't in the legend", number, key),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses a comma separated list of `fg <color>`, `bg <color>` and attribute names into a style
+pub(crate) fn parse_style(spec: &str) -> anyhow::Result<Style> {
+    let mut colors = Colors::default();
+    let mut attributes = Attributes::default();
+    for item in spec.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+        match item.split_once(char::is_whitespace) {
+            Some(("fg", color)) => colors.foreground = Some(parse_color(color.trim())?),
+            Some(("bg", color)) => colors.background = Some(parse_color(color.trim())?),
+            _ => attributes.set(parse_attribute(item)?),
+        }
+    }
+
+    Ok(Style::new(colors, attributes))
+}
+
+pub(crate) fn parse_color(name: &str) -> anyhow::Result<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |range: std::ops::Range<usize>| {
+            hex.get(range)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+                .ok_or_else(|| anyhow!("'{}' isn't a #rrggbb color", name))
+        };
+        if hex.len() != 6 {
+            bail!("'{}' isn't a #rrggbb color", name);
+        }
+        return Ok(Color::Rgb {
+            r: channel(0..2)?,
+            g: channel(2..4)?,
+            b: channel(4..6)?,
+        });
+    }
+
+    if let Ok(value) = name.parse::<u8>() {
+        return Ok(Color::AnsiValue(value));
+    }
+
+    match name {
+        "reset" => Ok(Color::Reset),
+        _ => Color::try_from(name).map_err(|_| anyhow!("'{}' isn't a color", name)),
+    }
+}
+
+/// Attributes are named the same as crossterm's, in any case and with or without underscores, so `crossed_out` and
+/// `CrossedOut` both work
+pub(crate) fn parse_attribute(name: &str) -> anyhow::Result<Attribute> {
+    let wanted = name.replace('_', "").to_lowercase();
+    Attribute::iterator()
+        .find(|attribute| format!("{:?}", attribute).to_lowercase() == wanted)
+        .ok_or_else(|| anyhow!("'{}' isn't an attribute", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error<T>(result: anyhow::Result<T>) -> String {
+        result.err().expect("expected an error").to_string()
+    }
+
+    #[test]
+    fn colors() {
+        let cases = [
+            ("red", Color::Red),
+            ("dark_grey", Color::DarkGrey),
+            ("reset", Color::Reset),
+            ("200", Color::AnsiValue(200)),
+            ("#20a0ff", Color::Rgb { r: 0x20, g: 0xa0, b: 0xff }),
+        ];
+        for (name, color) in cases {
+            assert_eq!(parse_color(name).unwrap(), color, "{}", name);
+        }
+
+        for name in ["#12345", "#1234567", "#12345g", "256", "purple"] {
+            assert!(parse_color(name).is_err(), "{}", name);
+        }
+        assert_eq!(error(parse_color("#12345g")), "'#12345g' isn't a #rrggbb color");
+    }
+
+    #[test]
+    fn attributes() {
+        for name in ["bold", "Bold", "crossed_out", "CrossedOut", "underlined"] {
+            assert!(parse_attribute(name).is_ok(), "{}", name);
+        }
+        assert_eq!(parse_attribute("crossed_out").unwrap(), Attribute::CrossedOut);
+        assert_eq!(error(parse_attribute("shiny")), "'shiny' isn't an attribute");
+    }
+
+    #[test]
+    fn styles() {
+        let style = parse_style("fg red, bg #202020, bold, underlined").unwrap();
+        assert_eq!(style.colors.foreground, Some(Color::Red));
+        assert_eq!(style.colors.background, Some(Color::Rgb { r: 0x20, g: 0x20, b: 0x20 }));
+        assert!(style.attributes.has(Attribute::Bold));
+        assert!(style.attributes.has(Attribute::Underlined));
+
+        assert_eq!(parse_style("").unwrap(), Style::new(Colors::default(), Attributes::default()));
+        assert!(parse_style("fg red bold").is_err());
+        assert!(parse_style("bg").is_err());
+    }
+
+    #[test]
+    fn map_lines_up_with_the_legend() {
+        let red = parse_style("fg red").unwrap();
+        let default = Style::default();
+        let lines = [(1, "R R  "), (2, " R"), (3, ""), (4, "  ")];
+        let map = map_from_legend(&[('R', red)], default, &lines).unwrap();
+
+        // Trailing spaces and blank lines at the end are left out
+        assert_eq!(map, vec![vec![red, default, red], vec![default, red]]);
+
+        let err = error(map_from_legend(&[('R', red)], default, &[(7, "RxR")]));
+        assert_eq!(err, "line 7: 'x' isn't in the legend");
+    }
+
+    #[test]
+    fn stylemaps() {
+        let stylemap = parse_stylemap("# comment\n[style]\nfg white\n\n[legend]\nb: fg blue\n\n[map]\nb b\n").unwrap();
+        assert_eq!(stylemap.style.colors.foreground, Some(Color::White));
+        assert_eq!(stylemap.style_at(0, 0).unwrap().colors.foreground, Some(Color::Blue));
+        assert_eq!(stylemap.style_at(1, 0).unwrap().colors.foreground, Some(Color::White));
+    }
+
+    #[test]
+    fn section_errors() {
+        let cases = [
+            ("[legend]\n[colours]\n", "line 2: unknown section [colours]"),
+            ("fg red\n[map]\n", "line 1: expected a section like [legend] first"),
+            ("[legend]\nR: fg red\n[map]\nRG\n", "line 4: 'G' isn't in the legend"),
+            ("[legend]\nR fg red\n", "line 2: expected a ':' after the 'R' in the legend"),
+            ("[legend]\nR: bg #ff00\n", "line 2: '#ff00' isn't a #rrggbb color"),
+            ("[style]\nfg sparkly\n", "line 2: 'sparkly' isn't a color"),
+        ];
+        for (text, message) in cases {
+            assert_eq!(error(parse_stylemap(text)), message, "{:?}", text);
+        }
+
+        // Anything in the map is content, even if it looks like a header
+        assert!(parse_stylemap("[legend]\n[: bold\nx: bold\n]: bold\n[map]\n[x]\n").is_ok());
+    }
+}
@@ -1,48 +1,173 @@
+// Bevy systems routinely take many parameters with long query types
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
 use bevy::prelude::*;
 use systems::crossterm_render;
 
+pub mod actions;
+pub mod animation;
+mod ansi_art;
 mod asset_loaders;
+pub mod backend;
+pub mod canvas;
+pub mod capabilities;
+mod buffer;
 pub mod components;
+pub mod exit;
+pub mod guard;
+mod half_block;
+pub mod headless;
+mod keyboard;
+mod legend;
+pub mod panel;
+pub mod paste;
+pub mod picking;
 pub mod prelude;
 mod runner;
+pub mod shapes;
+pub mod suspend;
 mod systems;
+pub mod text;
 
 pub struct CrosstermPlugin;
 impl Plugin for CrosstermPlugin {
     fn build(&self, app: &mut App) {
+        let window_settings = app
+            .world
+            .get_resource::<CrosstermWindowSettings>()
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some((width, height)) = window_settings.headless() {
+            let backend = headless::HeadlessBackend::new(width, height);
+            app.insert_resource(backend::Terminal::new(backend.clone()))
+                .insert_resource(backend);
+        }
+
+        // A virtual screen can show anything, and tests shouldn't depend on the environment they're run in
+        let color_depth = match (window_settings.color_depth(), window_settings.headless()) {
+            (Some(color_depth), _) => color_depth,
+            (None, Some(_)) => capabilities::ColorDepth::TrueColor,
+            (None, None) => capabilities::ColorDepth::detect(),
+        };
+
         app.insert_resource(Cursor::default())
-            .insert_resource(components::PreviousEntityDetails::default())
-            .insert_resource(components::EntitiesToRedraw::default())
-            .insert_resource(components::PreviousWindowColors::default())
+            .insert_resource(capabilities::TerminalCapabilities { color_depth })
+            .init_resource::<backend::Terminal>()
+            .init_resource::<buffer::ScreenBuffer>()
+            .init_resource::<bevy::input::Input<crossterm::event::KeyCode>>()
+            .insert_resource(keyboard::KeyboardState::new(window_settings.key_release_timeout()))
             .add_asset::<components::Sprite>()
             .add_asset::<components::StyleMap>()
+            .add_asset::<animation::SpriteAnimation>()
+            .add_asset::<actions::ActionBindings>()
             .init_asset_loader::<asset_loaders::SpriteLoader>()
             .init_asset_loader::<asset_loaders::StyleMapLoader>()
+            .init_asset_loader::<asset_loaders::SpriteAnimationLoader>()
+            .init_asset_loader::<asset_loaders::ActionBindingsLoader>()
+            .init_asset_loader::<asset_loaders::AnsiArtLoader>()
+            .init_asset_loader::<asset_loaders::TSpriteLoader>()
+            .init_asset_loader::<asset_loaders::TImageLoader>()
             .add_event::<crossterm::event::KeyEvent>()
             .add_event::<crossterm::event::MouseEvent>()
-            .set_runner(runner::crossterm_runner)
+            .add_event::<paste::PasteEvent>()
+            .add_event::<picking::PickingEvent>()
+            .add_event::<exit::ExitRequested>()
+            .add_event::<exit::CancelExit>()
+            .add_event::<exit::TerminalTeardown>()
+            .init_resource::<exit::ExitCode>()
+            .init_schedule(exit::Teardown)
+            .add_event::<suspend::Suspended>()
+            .add_event::<suspend::Resumed>()
+            .init_resource::<picking::PickingState>()
+            .add_event::<bevy::window::WindowCreated>()
+            .add_event::<bevy::window::WindowResized>()
+            .add_event::<bevy::window::WindowFocused>()
+            .add_event::<bevy::window::RequestRedraw>()
             // Systems and stages
-            // This must be before LAST because change tracking is cleared during LAST, but AssetEvents are published
-            // after POST_UPDATE. The timing for all these things is pretty delicate
+            // Rendering happens once every other system has had its say for the frame, and after assets that were
+            // changed this frame have been updated
             .configure_sets(
                 (
                     RenderSet::PreRender,
                     RenderSet::Render,
                     RenderSet::PostRender
-                ).before(CoreSet::Last)
+                )
+                    .chain()
+                    .after(bevy::asset::AssetSet::AssetEvents)
+                    .before(CoreSet::Last)
+            )
+            // Key events are turned into which keys are held, and mouse events into picking events, before the update
+            // so systems can react to them right away
+            .add_system(keyboard::update_keyboard.in_base_set(CoreSet::PreUpdate))
+            .add_system(picking::pick.in_base_set(CoreSet::PreUpdate))
+            // Animations are stepped after the update, so systems can start, stop or restart them first
+            .add_system(animation::animate.in_base_set(CoreSet::PostUpdate))
+            // Text, panels, shapes and canvases are laid out after the update, so they're drawn the same frame they're changed in
+            .add_system(text::layout_text.in_base_set(CoreSet::PostUpdate))
+            .add_system(panel::layout_panels.in_base_set(CoreSet::PostUpdate))
+            .add_system(shapes::layout_shapes.in_base_set(CoreSet::PostUpdate))
+            .add_system(canvas::layout_canvases.in_base_set(CoreSet::PostUpdate))
+            // Positions are settled after everything has had a chance to move during the update. Entities missing a
+            // global position get one first, and it's inserted straight away so they're drawn this frame
+            .add_systems(
+                (
+                    systems::insert_global_positions,
+                    apply_system_buffers,
+                    systems::propagate_positions,
+                )
+                    .chain()
+                    .in_base_set(CoreSet::PostUpdate),
             )
-            .add_system(systems::add_previous_position.in_base_set(CoreSet::PostUpdate))
-            // Needs asset events, and they aren't created until after POST_UPDATE, so we put them in PRE_RENDER
-            .add_system(systems::calculate_entities_to_redraw.in_base_set(RenderSet::PreRender))
-            .add_system(crossterm_render.in_base_set(RenderSet::Render))
-            .add_system(systems::update_previous_position.in_base_set(RenderSet::PostRender));
+            .add_system(systems::composite.in_base_set(RenderSet::PreRender))
+            // Exit requests can be cancelled by any system, so they're only acted on once every system has run
+            .add_system(exit::exit_on_request.in_base_set(CoreSet::Last))
+            .add_system(crossterm_render.in_base_set(RenderSet::Render));
+
+        // Plain images are only claimed when asked for, since bevy's own image loader wants the same extensions
+        if window_settings.load_images() {
+            app.init_asset_loader::<asset_loaders::HalfBlockLoader>();
+        }
+
+        if window_settings.headless().is_some() {
+            // There's no tty to drive the app, so the window is created up front and the app is stepped manually
+            // with app.update()
+            runner::setup_terminal(&mut app.world, &window_settings);
+        } else {
+            app.set_runner(runner::crossterm_runner);
+        }
     }
 }
 
+/// When the runner updates the app
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UpdateMode {
+    /// Update on the schedule set by bevy's `ScheduleRunnerSettings`, whatever happens. Input is handled by the next
+    /// scheduled update
+    #[default]
+    Continuous,
+    /// Update on schedule, and also as soon as there's input. The runner sleeps until one or the other, so input is
+    /// handled without waiting for the rest of the frame
+    Reactive,
+    /// Only update when there's input, the terminal is resized or loses focus, or once `max_wait` has passed since the
+    /// last update. A system can send bevy's `RequestRedraw` event to get another update on schedule, which playing
+    /// animations do. Nothing else wakes the app, including assets finishing loading, so this suits dashboards and
+    /// tools that should be idle until something happens
+    OnDemand { max_wait: Option<std::time::Duration> },
+}
+
 #[derive(Clone, Eq, PartialEq, Resource)]
 pub struct CrosstermWindowSettings {
     colors: components::Colors,
     title: Option<String>,
+    headless: Option<(u16, u16)>,
+    exit_behavior: exit::ExitBehavior,
+    suspend_chord: Option<exit::KeyChord>,
+    inline: Option<u16>,
+    color_depth: Option<capabilities::ColorDepth>,
+    key_release_timeout: std::time::Duration,
+    update_mode: UpdateMode,
+    load_images: bool,
 }
 
 impl Default for CrosstermWindowSettings {
@@ -50,6 +175,14 @@ impl Default for CrosstermWindowSettings {
         CrosstermWindowSettings {
             colors: components::Colors::term_colors(),
             title: None,
+            headless: None,
+            exit_behavior: exit::ExitBehavior::default(),
+            suspend_chord: Some(exit::KeyChord::ctrl('z')),
+            inline: None,
+            color_depth: None,
+            key_release_timeout: std::time::Duration::from_millis(700),
+            update_mode: UpdateMode::default(),
+            load_images: false,
         }
     }
 }
@@ -72,6 +205,99 @@ impl CrosstermWindowSettings {
         self.colors = colors;
         self
     }
+
+    pub fn exit_behavior(&self) -> exit::ExitBehavior {
+        self.exit_behavior
+    }
+
+    /// Choose what happens when the exit chord is pressed. By default Control-C exits the app straight away
+    pub fn set_exit_behavior(&mut self, exit_behavior: exit::ExitBehavior) -> &mut Self {
+        self.exit_behavior = exit_behavior;
+        self
+    }
+
+    pub fn suspend_chord(&self) -> Option<exit::KeyChord> {
+        self.suspend_chord
+    }
+
+    /// Choose the chord that suspends the app back to the shell, or None to let the app have it. Defaults to
+    /// Control-Z, which raw mode would otherwise swallow. Only has an effect on unix
+    pub fn set_suspend_chord(&mut self, chord: Option<exit::KeyChord>) -> &mut Self {
+        self.suspend_chord = chord;
+        self
+    }
+
+    /// The height of the inline viewport, or None when taking over the whole terminal
+    pub fn inline(&self) -> Option<u16> {
+        self.inline
+    }
+
+    /// Render into a viewport this many rows tall just below the cursor, instead of switching to the alternate screen.
+    /// The window is the size of the viewport and everything is positioned relative to it. Whatever was drawn last is
+    /// left in the scrollback when the app exits, which suits command line tools. The mouse isn't captured inline, so
+    /// the terminal's own scrolling and selection keep working
+    pub fn set_inline(&mut self, height: u16) -> &mut Self {
+        self.inline = Some(height);
+        self
+    }
+
+    /// The color depth chosen with `set_color_depth`, or None if it's detected from the environment
+    pub fn color_depth(&self) -> Option<capabilities::ColorDepth> {
+        self.color_depth
+    }
+
+    /// Render with this color depth instead of detecting what the terminal supports. Useful for seeing what an app
+    /// looks like on a more limited terminal
+    pub fn set_color_depth(&mut self, color_depth: capabilities::ColorDepth) -> &mut Self {
+        self.color_depth = Some(color_depth);
+        self
+    }
+
+    pub fn update_mode(&self) -> UpdateMode {
+        self.update_mode
+    }
+
+    /// Choose when the app is updated. By default it's updated on a fixed schedule
+    pub fn set_update_mode(&mut self, update_mode: UpdateMode) -> &mut Self {
+        self.update_mode = update_mode;
+        self
+    }
+
+    pub fn key_release_timeout(&self) -> std::time::Duration {
+        self.key_release_timeout
+    }
+
+    /// How long a key stays held in `Input<KeyCode>` after it was last pressed, on terminals that only report key
+    /// presses. Held keys are pressed again after the keyboard's repeat delay, so this has to be longer than that or
+    /// they'll flicker. Defaults to 700ms, which is longer than most repeat delays
+    pub fn set_key_release_timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
+        self.key_release_timeout = timeout;
+        self
+    }
+
+    pub fn load_images(&self) -> bool {
+        self.load_images
+    }
+
+    /// Load PNG, BMP, TGA and PNM images straight into half block sprites. Off by default, because bevy's own image
+    /// loader claims the same extensions, and an app using both would get whichever loader was added last. `.timage`
+    /// files work either way
+    pub fn set_load_images(&mut self, load_images: bool) -> &mut Self {
+        self.load_images = load_images;
+        self
+    }
+
+    /// The size of the virtual screen when running headless, or None when drawing to a real terminal
+    pub fn headless(&self) -> Option<(u16, u16)> {
+        self.headless
+    }
+
+    /// Render into a virtual screen of the given size instead of a terminal. The plugin won't install its runner, so
+    /// the app is driven by calling app.update(), and the screen can be read back from the `HeadlessBackend` resource.
+    pub fn set_headless(&mut self, width: u16, height: u16) -> &mut Self {
+        self.headless = Some((width, height));
+        self
+    }
 }
 
 #[derive(Debug, Component)]
@@ -80,6 +306,9 @@ pub struct CrosstermWindow {
     width: u16,
     colors: components::Colors,
     title: Option<String>,
+    // The terminal row the window starts on, which is only ever not 0 for an inline viewport
+    origin: u16,
+    focused: bool,
 }
 
 impl Default for CrosstermWindow {
@@ -87,17 +316,22 @@ impl Default for CrosstermWindow {
         let (width, height) =
             crossterm::terminal::size().expect("Could not read current terminal size");
 
-        let colors = components::Colors::term_colors();
+        CrosstermWindow::with_size(width, height)
+    }
+}
+
+impl CrosstermWindow {
+    pub(crate) fn with_size(width: u16, height: u16) -> CrosstermWindow {
         CrosstermWindow {
             height,
             width,
-            colors,
+            colors: components::Colors::term_colors(),
             title: None,
+            origin: 0,
+            focused: true,
         }
     }
-}
 
-impl CrosstermWindow {
     pub fn height(&self) -> u16 {
         self.height
     }
@@ -114,6 +348,11 @@ impl CrosstermWindow {
         self.colors
     }
 
+    /// Whether the terminal has focus. Terminals that don't report focus changes are always focused
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
     pub fn set_colors(&mut self, new_colors: components::Colors) {
         self.colors = new_colors;
     }
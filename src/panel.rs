@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+
+use crate::components::{grapheme_width, GlobalPosition, Position, Sprite, Style, StyleMap, Visible};
+use crate::systems;
+use crate::text::{Text, TextOverflow};
+
+/// A sprite that's cut into nine pieces to draw a border. The corners are drawn as they are, the edges are repeated
+/// along the sides of the panel, and the middle is repeated to fill it in
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NineSlice {
+    pub sprite: Handle<Sprite>,
+    /// How many columns and lines from each edge of the sprite belong to the border, in the order left, top, right,
+    /// bottom
+    pub insets: (usize, usize, usize, usize),
+}
+
+impl NineSlice {
+    /// A nine-slice of a sprite whose border is one cell thick, like a 3x3 sprite
+    pub fn new(sprite: Handle<Sprite>) -> NineSlice {
+        NineSlice {
+            sprite,
+            insets: (1, 1, 1, 1),
+        }
+    }
+
+    pub fn with_insets(mut self, left: usize, top: usize, right: usize, bottom: usize) -> NineSlice {
+        self.insets = (left, top, right, bottom);
+        self
+    }
+}
+
+/// What the edge of a panel is drawn with
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Border {
+    /// `┌─┐`
+    #[default]
+    Single,
+    /// `╔═╗`
+    Double,
+    /// `╭─╮`
+    Rounded,
+    /// `┏━┓`
+    Heavy,
+    /// `+-+`, for terminals without box drawing characters
+    Ascii,
+    NineSlice(NineSlice),
+}
+
+/// A box with a border, drawn into the entity's sprite and stylemap whenever it changes, so it can be resized. The
+/// width and height include the border. The inside is left as unstyled spaces, so a transparent panel is only a frame.
+///
+/// Like `Text`, the panel's sprite and stylemap belong to it and shouldn't be shared with other entities.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Component)]
+pub struct Panel {
+    pub width: usize,
+    pub height: usize,
+    pub border: Border,
+    /// The style of the whole panel
+    pub style: Style,
+    /// Drawn over the top edge, cut off with an ellipsis if it doesn't fit
+    pub title: Option<String>,
+    /// The style of the title, on top of the panel's style. Any colors it sets replace the panel's, and its attributes
+    /// are added to the panel's
+    pub title_style: Style,
+}
+
+impl Panel {
+    pub fn new(width: usize, height: usize) -> Panel {
+        Panel {
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_border(mut self, border: Border) -> Panel {
+        self.border = border;
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Panel {
+        self.style = style;
+        self
+    }
+
+    pub fn with_title<T: std::string::ToString>(mut self, title: T) -> Panel {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_title_style(mut self, title_style: Style) -> Panel {
+        self.title_style = title_style;
+        self
+    }
+
+    /// Draws the panel into a sprite and a stylemap. Panels with a nine-slice border can't be drawn until the sprite
+    /// for it has loaded, and until then this is None
+    pub fn layout(&self, sprites: &Assets<Sprite>) -> Option<(Sprite, StyleMap)> {
+        let preset = |text: &str| (Sprite::new(text), (1, 1, 1, 1));
+        let (source, (left, top, right, bottom)) = match &self.border {
+            Border::Single => preset("┌─┐\n│ │\n└─┘"),
+            Border::Double => preset("╔═╗\n║ ║\n╚═╝"),
+            Border::Rounded => preset("╭─╮\n│ │\n╰─╯"),
+            Border::Heavy => preset("┏━┓\n┃ ┃\n┗━┛"),
+            Border::Ascii => preset("+-+\n| |\n+-+"),
+            Border::NineSlice(nine_slice) => {
+                let sprite = sprites.get(&nine_slice.sprite)?;
+                (Sprite::new(sprite.data()), nine_slice.insets)
+            }
+        };
+        let source = cells(&source);
+        let source_height = source.len();
+        let source_width = source.first().map_or(0, Vec::len);
+
+        let mut rows: Vec<Vec<&str>> = (0..self.height)
+            .map(|y| {
+                let line = slice(y, self.height, source_height, top, bottom);
+                (0..self.width)
+                    .map(|x| {
+                        let column = slice(x, self.width, source_width, left, right);
+                        match (line, column) {
+                            (Some(line), Some(column)) => source[line][column].as_str(),
+                            _ => " ",
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // The title goes between the top corners, with a space either side of it. Its attributes are added to the
+        // panel's, since a stylemap's own style only applies to cells that the map doesn't cover
+        let mut map = Vec::new();
+        let mut title_style = self.title_style;
+        title_style.attributes.extend(self.style.attributes);
+        let title = self.title.as_ref().map(|title| {
+            Text::from_section(format!(" {} ", title), title_style)
+                .with_max_width(self.width.saturating_sub(left + right))
+                .with_overflow(TextOverflow::Ellipsis)
+                .layout()
+        });
+        if let (Some((title, title_styles)), Some(row)) = (&title, rows.first_mut()) {
+            let mut styles = vec![self.style; left];
+            for (column, grapheme) in title.columns(0) {
+                row[left + column] = grapheme;
+                if grapheme_width(grapheme) == 2 {
+                    row[left + column + 1] = "";
+                }
+            }
+            styles.extend(title_styles.map.first().into_iter().flatten());
+            map.push(styles);
+        }
+
+        let data = rows.iter().map(|row| join(row)).collect::<Vec<_>>().join("\n");
+        Some((Sprite::new(data), StyleMap::new(self.style, map)))
+    }
+}
+
+/// The graphemes of a sprite laid out by column, padded out with spaces into a rectangle. The second column of a wide
+/// grapheme is empty
+fn cells(sprite: &Sprite) -> Vec<Vec<String>> {
+    (0..sprite.height())
+        .map(|line| {
+            let mut row = vec![" ".to_string(); sprite.width()];
+            for (column, grapheme) in sprite.columns(line) {
+                row[column] = grapheme.to_string();
+                if grapheme_width(grapheme) == 2 {
+                    row[column + 1] = String::new();
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+/// Which column (or line) of a nine-slice is drawn at a position along a panel that's `size` long, where the nine-slice
+/// is `source` long and its border takes up `start` and `end` of that. None means there's nothing to draw there
+fn slice(position: usize, size: usize, source: usize, start: usize, end: usize) -> Option<usize> {
+    if position < start {
+        return (position < source).then_some(position);
+    }
+    if size - position <= end {
+        return source.checked_sub(size - position);
+    }
+
+    let middle = source.checked_sub(start + end).filter(|middle| *middle > 0)?;
+    Some(start + (position - start) % middle)
+}
+
+/// Joins a row of cells back together. Slicing can split a wide grapheme from its second column, and the half that's
+/// left over is drawn as a space
+fn join(row: &[&str]) -> String {
+    let mut line = String::new();
+    let mut column = 0;
+    while column < row.len() {
+        let grapheme = row[column];
+        if grapheme_width(grapheme) == 2 && row.get(column + 1) == Some(&"") {
+            line.push_str(grapheme);
+            column += 2;
+            continue;
+        }
+
+        if grapheme.is_empty() || grapheme_width(grapheme) == 2 {
+            line.push(' ');
+        } else {
+            line.push_str(grapheme);
+        }
+        column += 1;
+    }
+    line
+}
+
+/// An entity that displays a panel. See `Panel`
+#[derive(Bundle, Default)]
+pub struct PanelBundle {
+    pub panel: Panel,
+    pub sprite: Handle<Sprite>,
+    pub position: Position,
+    pub global_position: GlobalPosition,
+    pub stylemap: Handle<StyleMap>,
+    pub visible: Visible,
+}
+
+pub(crate) fn layout_panels(
+    mut sprite_events: EventReader<AssetEvent<Sprite>>,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+    mut panels: Query<(Ref<Panel>, &mut Handle<Sprite>, &mut Handle<StyleMap>)>,
+) {
+    // Panels are drawn again when the sprite they're sliced from loads or changes
+    let changed: Vec<Handle<Sprite>> = sprite_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle.clone_weak()),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+
+    for (panel, mut sprite_handle, mut stylemap_handle) in panels.iter_mut() {
+        let sliced_changed = matches!(
+            &panel.border,
+            Border::NineSlice(nine_slice) if changed.contains(&nine_slice.sprite)
+        );
+        if !panel.is_changed() && !sliced_changed {
+            continue;
+        }
+
+        if let Some((sprite, stylemap)) = panel.layout(&sprites) {
+            systems::replace_assets(
+                (&mut sprites, &mut sprite_handle),
+                (&mut stylemaps, &mut stylemap_handle),
+                sprite,
+                stylemap,
+            );
+        }
+    }
+}
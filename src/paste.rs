@@ -0,0 +1,4 @@
+/// Text pasted into the terminal, all in one piece. Pasted text doesn't also arrive as key events, so a newline in it
+/// won't set off whatever Enter is bound to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasteEvent(pub String);
@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+use crate::components::{grapheme_width, GlobalPosition, Sprite, StyleMap, Visible};
+
+/// Marks an entity as something the mouse can interact with. Only entities with this component produce
+/// `PickingEvent`s, and they're hit tested against the cells they'd actually draw to, so the holes in transparent
+/// sprites don't count. Anything drawn on top of a pickable entity, pickable or not, stops the mouse reaching it.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct Pickable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickingKind {
+    /// The mouse moved onto the entity
+    HoverEnter,
+    /// The mouse moved off of the entity
+    HoverLeave,
+    Pressed(MouseButton),
+    Released(MouseButton),
+    /// A button was both pressed and released over the entity
+    Clicked(MouseButton),
+    /// The mouse moved while a button that was pressed over the entity is held. This keeps being sent even once the
+    /// mouse has left the entity, until the button is released
+    Dragged(MouseButton),
+}
+
+/// Sent when the mouse interacts with a `Pickable` entity. The column and row are where the mouse is on the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PickingEvent {
+    pub entity: Entity,
+    pub kind: PickingKind,
+    pub column: u16,
+    pub row: u16,
+    pub modifiers: KeyModifiers,
+}
+
+#[derive(Default, Resource)]
+pub(crate) struct PickingState {
+    mouse: Option<(u16, u16)>,
+    hovered: Option<Entity>,
+    pressed: HashMap<MouseButton, Entity>,
+}
+
+/// Checks whether an entity draws anything to the cell at x, y
+fn covers(
+    pos: &GlobalPosition,
+    sprite: &Sprite,
+    stylemap: &StyleMap,
+    visible: &Visible,
+    x: i32,
+    y: i32,
+) -> bool {
+    let (anchor_x, anchor_y) = sprite.anchor();
+    let column = x - pos.x + anchor_x;
+    let line = y - pos.y + anchor_y;
+    if !visible.is_visible
+        || column < 0
+        || line < 0
+        || column >= sprite.width() as i32
+        || line >= sprite.height() as i32
+    {
+        return false;
+    }
+
+    let grapheme = sprite
+        .columns(line as usize)
+        .find(|(start, grapheme)| (column as usize) < start + grapheme_width(grapheme))
+        .filter(|(start, _)| column as usize >= *start)
+        .map(|(_, grapheme)| grapheme);
+    if grapheme.is_some_and(|grapheme| sprite.is_transparent_grapheme(grapheme)) {
+        return false;
+    }
+
+    if !visible.is_transparent || stylemap.style_at(column as usize, line as usize).is_some() {
+        return true;
+    }
+
+    // Unstyled spaces are holes in a transparent sprite, and so is the padding past the end of a short line
+    grapheme.is_some_and(|grapheme| grapheme != " ")
+}
+
+pub(crate) fn pick(
+    mut state: ResMut<PickingState>,
+    mut mouse_events: EventReader<MouseEvent>,
+    mut picking_events: EventWriter<PickingEvent>,
+    sprites: Res<Assets<Sprite>>,
+    stylemaps: Res<Assets<StyleMap>>,
+    drawn: Query<(
+        Entity,
+        &GlobalPosition,
+        &Handle<Sprite>,
+        &Handle<StyleMap>,
+        &Visible,
+        Option<&Pickable>,
+    )>,
+) {
+    // The topmost entity drawn at a cell, using the same ordering as drawing does, if it's pickable. Entities that
+    // aren't pickable still block the ones underneath them
+    let topmost = |column: u16, row: u16| {
+        drawn
+            .iter()
+            .filter(|(_, pos, sprite, stylemap, visible, _)| {
+                match (sprites.get(sprite), stylemaps.get(stylemap)) {
+                    (Some(sprite), Some(stylemap)) => {
+                        covers(pos, sprite, stylemap, visible, column as i32, row as i32)
+                    }
+                    _ => false,
+                }
+            })
+            .max_by_key(|(entity, pos, _, _, _, _)| (pos.z, *entity))
+            .filter(|(_, _, _, _, _, pickable)| pickable.is_some())
+            .map(|(entity, _, _, _, _, _)| entity)
+    };
+
+    let mut events = Vec::new();
+    let mut moved = false;
+    for event in mouse_events.iter() {
+        let (column, row) = (event.column, event.row);
+        let picked = |entity, kind| PickingEvent {
+            entity,
+            kind,
+            column,
+            row,
+            modifiers: event.modifiers,
+        };
+
+        let hit = topmost(column, row);
+        state.mouse = Some((column, row));
+        moved = true;
+        update_hover(&mut state, hit, (column, row, event.modifiers), &mut events);
+
+        match event.kind {
+            MouseEventKind::Down(button) => {
+                if let Some(entity) = hit {
+                    events.push(picked(entity, PickingKind::Pressed(button)));
+                    state.pressed.insert(button, entity);
+                } else {
+                    state.pressed.remove(&button);
+                }
+            }
+            MouseEventKind::Up(button) => {
+                let pressed = state.pressed.remove(&button);
+                if let Some(entity) = hit {
+                    events.push(picked(entity, PickingKind::Released(button)));
+                    if pressed == Some(entity) {
+                        events.push(picked(entity, PickingKind::Clicked(button)));
+                    }
+                }
+            }
+            MouseEventKind::Drag(button) => {
+                if let Some(entity) = state.pressed.get(&button) {
+                    events.push(picked(*entity, PickingKind::Dragged(button)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Things can move around underneath a mouse that's sitting still, so the hover is checked every frame
+    if let (false, Some((column, row))) = (moved, state.mouse) {
+        let hit = topmost(column, row);
+        update_hover(&mut state, hit, (column, row, KeyModifiers::NONE), &mut events);
+    }
+
+    picking_events.send_batch(events);
+}
+
+/// Sends leave and enter events if the entity under the mouse changed
+fn update_hover(
+    state: &mut PickingState,
+    hit: Option<Entity>,
+    (column, row, modifiers): (u16, u16, KeyModifiers),
+    events: &mut Vec<PickingEvent>,
+) {
+    if state.hovered == hit {
+        return;
+    }
+
+    let hover = |entity, kind| PickingEvent {
+        entity,
+        kind,
+        column,
+        row,
+        modifiers,
+    };
+    if let Some(entity) = state.hovered {
+        events.push(hover(entity, PickingKind::HoverLeave));
+    }
+    if let Some(entity) = hit {
+        events.push(hover(entity, PickingKind::HoverEnter));
+    }
+    state.hovered = hit;
+}
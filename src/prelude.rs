@@ -1,9 +1,25 @@
-pub use crate::{CrosstermPlugin, CrosstermWindow, Cursor, CrosstermWindowSettings};
+pub use crate::actions::{Action, ActionBindings, ActionPlugin, ActionState, Binding, InputMap};
+pub use crate::animation::{AnimationMode, AnimationPlayer, Frame, SpriteAnimation};
+pub use crate::backend::{MemoryBackend, StdoutBackend, Terminal, TerminalBackend};
+pub use crate::canvas::{Canvas, CanvasBundle, CanvasMode};
+pub use crate::capabilities::{ColorDepth, TerminalCapabilities};
+pub use crate::exit::{
+    last_teardown, CancelExit, ExitBehavior, ExitCode, ExitReason, ExitRequested, KeyChord, Teardown, TerminalTeardown,
+};
+pub use crate::guard::TerminalGuard;
+pub use crate::headless::{Cell, HeadlessBackend};
+pub use crate::panel::{Border, NineSlice, Panel, PanelBundle};
+pub use crate::paste::PasteEvent;
+pub use crate::picking::{Pickable, PickingEvent, PickingKind};
+pub use crate::shapes::{Shape, ShapeBundle, ShapeKind};
+pub use crate::suspend::{Resumed, Suspended};
+pub use crate::text::{Text, TextAlignment, TextBundle, TextOverflow, TextSection};
+pub use crate::{CrosstermPlugin, CrosstermWindow, Cursor, CrosstermWindowSettings, UpdateMode};
 
 pub use crate::components::{
-    Color, Colors, Position, Sprite, SpriteBundle, Style, StyleMap, Visible,
+    Color, Colors, GlobalPosition, Position, Sprite, SpriteBundle, Style, StyleMap, Visible,
 };
 
 // Re-export crossterm structs for easier access
-pub use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent};
+pub use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent};
 pub use crossterm::style::{Attribute, Attributes};
@@ -1,9 +1,20 @@
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::{CrosstermWindow, CrosstermWindowSettings};
+use crate::backend::Terminal;
+use crate::buffer::ScreenBuffer;
+use crate::capabilities::TerminalCapabilities;
+use crate::exit::{self, ExitBehavior, ExitCode, ExitReason, ExitRequested, Teardown, TerminalTeardown};
+use crate::guard::{self, SignalHandlers, TerminalGuard};
+use crate::paste::PasteEvent;
+use crate::keyboard::KeyboardState;
+use crate::suspend::{self, Resumed, Suspended};
+use crate::{CrosstermWindow, CrosstermWindowSettings, UpdateMode};
 
-use bevy::{app::{App, AppExit}, ecs::event::Events, window::PrimaryWindow, prelude::With};
-use bevy::window::{WindowCreated, WindowResized};
+use bevy::{app::{App, AppExit}, ecs::event::{Events, ManualEventReader}, window::PrimaryWindow, prelude::{Entity, With, World}};
+use bevy::log::warn;
+use bevy::window::{RequestRedraw, WindowCreated, WindowFocused, WindowResized};
 use crossterm::{ExecutableCommand, QueueableCommand};
 
 pub fn crossterm_runner(mut app: App) {
@@ -16,59 +27,42 @@ pub fn crossterm_runner(mut app: App) {
         .get_resource_or_insert_with(CrosstermWindowSettings::default)
         .clone();
 
-    let mut term = std::io::stdout();
-
-    term.queue(crossterm::terminal::EnterAlternateScreen)
-        .unwrap();
-    term.queue(crossterm::event::EnableMouseCapture).unwrap();
-
-    crossterm::terminal::enable_raw_mode().expect("Could not enable crossterm raw mode");
-
-    let mut window = CrosstermWindow::default();
-
-    // Use settings in window
-    {
-        if let Some(title) = &window_settings.title() {
-            window.title = Some(title.clone());
-            term.queue(crossterm::terminal::SetTitle(&title))
-                .expect("Could not set terminal title");
+    // Makes sure a real terminal is usable again even if the app panics or this returns early. It's armed before the
+    // terminal is set up, so a panic or signal part way through setting up is covered too
+    let terminal_guard = app.world.get_resource_or_insert_with(Terminal::default).is_tty().then(|| {
+        match window_settings.inline() {
+            Some(_) => TerminalGuard::inline(),
+            None => TerminalGuard::new(),
         }
+    });
+    let mut signal_handlers = SignalHandlers::default();
+    let terminate = guard::termination_flag(&mut signal_handlers);
+    let suspend = suspend::suspend_flag(&mut signal_handlers);
+    wake_on_signals(&mut signal_handlers);
 
-        window.colors = window_settings.colors();
-        term.queue(crossterm::style::SetColors(window.colors.to_crossterm()))
-            .expect("Could not set window colors");
-    }
-
-    // Insert our window resources so that other parts of our app can use them
-    let window_id = app.world.spawn(window).insert(PrimaryWindow).id();
-    // app.insert_resource(window);
-
-    term.queue(crossterm::terminal::Clear(
-        crossterm::terminal::ClearType::All,
-    ))
-    .expect("Could not clear screen");
-
-    term.flush().unwrap();
-
-    // Publish to the app that a terminal window has been created
-    {
-        let mut window_created_events = app.world.get_resource_mut::<Events<WindowCreated>>().unwrap();
-        window_created_events.send(WindowCreated {
-            window: window_id,
-        });
-    }
+    let window_id = setup_terminal(&mut app.world, &window_settings);
 
-    match settings.run_mode {
+    let reason = match settings.run_mode {
         bevy::app::RunMode::Once => {
             app.update();
+            ExitReason::Finished
         }
         bevy::app::RunMode::Loop { wait } => {
             // Main loop
-            let tick = move |app: &mut App,
-                             wait: Option<std::time::Duration>|
-                  -> Result<Option<std::time::Duration>, AppExit> {
+            let tick = |app: &mut App,
+                        wait: Option<std::time::Duration>|
+             -> Result<Option<std::time::Duration>, AppExit> {
                 let start_time = std::time::Instant::now();
 
+                // Being asked to terminate is treated like any other exit, so the terminal gets torn down properly
+                if terminate.load(Ordering::SeqCst) {
+                    app.world.send_event(AppExit);
+                }
+
+                if suspend.swap(false, Ordering::SeqCst) {
+                    suspend_app(app, window_id, &window_settings);
+                }
+
                 // Check if any events are immediately available and if so, read them and republish
                 while let Ok(available) = crossterm::event::poll(std::time::Duration::from_secs(0))
                 {
@@ -76,15 +70,27 @@ pub fn crossterm_runner(mut app: App) {
                         match crossterm::event::read().unwrap() {
                             // Republish keyboard events in bevy
                             crossterm::event::Event::Key(key_event) => {
-                                // If the key event is for C-c, submit a AppExit event so the application
-                                // can be killed
-                                use crossterm::event::{KeyCode, KeyModifiers};
-                                if key_event.code == KeyCode::Char('c')
-                                    && key_event.modifiers.contains(KeyModifiers::CONTROL)
+                                // Raw mode stops the terminal from turning the suspend chord into SIGTSTP, so it's
+                                // done by hand. The key press is used up by this and isn't passed on to the app
+                                if cfg!(unix)
+                                    && window_settings
+                                        .suspend_chord()
+                                        .is_some_and(|chord| chord.matches(&key_event))
                                 {
-                                    let mut app_exit_events =
-                                        app.world.get_resource_mut::<Events<AppExit>>().unwrap();
-                                    app_exit_events.send(AppExit);
+                                    suspend_app(app, window_id, &window_settings);
+                                    continue;
+                                }
+
+                                // If the key event is the exit chord, either exit straight away or ask the app
+                                // whether it's alright to
+                                match window_settings.exit_behavior() {
+                                    ExitBehavior::Exit(chord) if chord.matches(&key_event) => {
+                                        app.world.send_event(AppExit);
+                                    }
+                                    ExitBehavior::Request(chord) if chord.matches(&key_event) => {
+                                        app.world.send_event(ExitRequested);
+                                    }
+                                    _ => {}
                                 }
 
                                 let mut bevy_key_events = app
@@ -105,23 +111,29 @@ pub fn crossterm_runner(mut app: App) {
 
                             // Send a bevy window resized event if the terminal is resized, and also change the persisted window state
                             crossterm::event::Event::Resize(width, height) => {
-                                // Update the window resource and publish an event for the window being resized
-                                let mut window_resized_events =
-                                    app.world.get_resource_mut::<Events<WindowResized>>().unwrap();
-                                window_resized_events.send(WindowResized {
-                                    window: window_id,
-                                    width: width as f32,
-                                    height: height as f32,
-                                });
-
-                                let mut window =
-                                    app.world.query_filtered::<&mut CrosstermWindow, With<PrimaryWindow>>()
-                                    .single_mut(&mut app.world);
-                                window.as_mut().height = height;
-                                window.as_mut().width = width;
+                                // An inline viewport keeps its height while it fits, and moves up if the terminal
+                                // got too short for where it was
+                                let (height, origin) = match window_settings.inline() {
+                                    Some(rows) => {
+                                        let rows = rows.min(height);
+                                        let window = app
+                                            .world
+                                            .query_filtered::<&CrosstermWindow, With<PrimaryWindow>>()
+                                            .single(&app.world);
+                                        (rows, window.origin.min(height - rows))
+                                    }
+                                    None => (height, 0),
+                                };
+                                resize_window(app, window_id, width, height, origin);
                             },
-                            // NOTE: Ignore other events for now
-                            _ => {}
+
+                            // Republish pasted text in bevy
+                            crossterm::event::Event::Paste(text) => {
+                                app.world.send_event(PasteEvent(text));
+                            }
+
+                            crossterm::event::Event::FocusGained => focus_window(app, window_id, true),
+                            crossterm::event::Event::FocusLost => focus_window(app, window_id, false),
                         }
                     } else {
                         break;
@@ -136,7 +148,7 @@ pub fn crossterm_runner(mut app: App) {
                 {
                     let app_exit_events = app.world.get_resource::<Events<AppExit>>().unwrap();
                     let mut app_exit_reader = app_exit_events.get_reader();
-                    if app_exit_reader.iter(&app_exit_events).next().is_some() {
+                    if app_exit_reader.iter(app_exit_events).next().is_some() {
                         // We're breaking out, the app requested an exit
                         return Err(AppExit);
                     };
@@ -155,20 +167,308 @@ pub fn crossterm_runner(mut app: App) {
                 Ok(None)
             };
 
-            // Run the main loop, and delay if we need to
+            // Run the main loop, and wait for the next update in whichever way the update mode calls for
+            let mut redraw_requests = ManualEventReader::<RequestRedraw>::default();
             while let Ok(delay) = tick(&mut app, wait) {
-                if let Some(delay) = delay {
-                    std::thread::sleep(delay);
+                let redraw_requested = redraw_requests
+                    .iter(app.world.resource::<Events<RequestRedraw>>())
+                    .count()
+                    > 0;
+
+                match window_settings.update_mode() {
+                    UpdateMode::Continuous => {
+                        if let Some(delay) = delay {
+                            std::thread::sleep(delay);
+                        }
+                    }
+                    UpdateMode::Reactive => {
+                        if let Some(delay) = delay {
+                            wait_for_event(Some(delay), &terminate, &suspend);
+                        }
+                    }
+                    UpdateMode::OnDemand { max_wait } => {
+                        if !redraw_requested {
+                            wait_for_event(max_wait, &terminate, &suspend);
+                        } else if let Some(delay) = delay {
+                            wait_for_event(Some(delay), &terminate, &suspend);
+                        }
+                    }
                 }
             }
 
-            // Cleanup and teardown
-            term.execute(crossterm::event::DisableMouseCapture)
-                .expect("Could not disable mouse capture");
-            crossterm::terminal::disable_raw_mode().expect("Could not disable raw mode");
-            term.execute(crossterm::terminal::LeaveAlternateScreen)
-                .unwrap();
-            term.execute(crossterm::cursor::Show).unwrap();
+            match terminate.load(Ordering::SeqCst) {
+                true => ExitReason::Terminated,
+                false => ExitReason::Exited,
+            }
         }
+    };
+
+    // Cleanup and teardown, however the app stopped
+    leave_terminal(&mut app.world, &window_settings);
+    if let Some(terminal_guard) = terminal_guard {
+        terminal_guard.disarm();
     }
+    // Signals are back to their usual behavior while tearing down and once the runner has returned
+    signal_handlers.unregister();
+
+    // Only the teardown schedule is run, so the app can say goodbye on the normal screen without another full update
+    let exit_code = app.world.resource::<ExitCode>().0;
+    app.world.send_event(TerminalTeardown { reason, exit_code });
+    app.world.run_schedule(Teardown);
+
+    // The app can still change its mind about the exit code while tearing down
+    let exit_code = app.world.resource::<ExitCode>().0;
+    exit::set_last_teardown(TerminalTeardown { reason, exit_code });
+}
+
+// Signals wake up waiting for input by themselves, see `wake_on_signals`. The flags they set are checked this often
+// as well, in case a wakeup ever goes missing, which is rare enough that idle apps can sleep for this long
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Signals don't interrupt waiting for input on their own. Raising SIGWINCH when one arrives does, since crossterm
+/// wakes up to check the terminal's size, so the runner gets to the signal's flag straight away. The resize event that
+/// comes with it is the size the terminal already was, and doesn't change anything
+#[cfg(unix)]
+fn wake_on_signals(handlers: &mut SignalHandlers) {
+    use signal_hook::consts::{SIGHUP, SIGTERM, SIGTSTP, SIGWINCH};
+
+    for signal in [SIGTERM, SIGHUP, SIGTSTP] {
+        // Safe because raising a signal is async-signal-safe, and that's all the handler does
+        let handler = unsafe {
+            signal_hook::low_level::register(signal, || {
+                let _ = signal_hook::low_level::raise(SIGWINCH);
+            })
+        };
+        handlers.add(signal, handler);
+    }
+}
+
+#[cfg(not(unix))]
+fn wake_on_signals(_handlers: &mut SignalHandlers) {}
+
+/// Sleeps until there's an event to read from the terminal, a signal needs handling, or the timeout runs out. Without a
+/// timeout this only returns for events and signals
+fn wait_for_event(timeout: Option<Duration>, terminate: &AtomicBool, suspend: &AtomicBool) {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    while !terminate.load(Ordering::SeqCst) && !suspend.load(Ordering::SeqCst) {
+        let interval = match deadline {
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .min(SIGNAL_CHECK_INTERVAL),
+            None => SIGNAL_CHECK_INTERVAL,
+        };
+        // An error is left for the next update to run into when it reads the event
+        if crossterm::event::poll(interval).unwrap_or(true) {
+            return;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return;
+        }
+    }
+}
+
+/// Prepares the terminal for drawing, and spawns the primary window that represents it
+pub(crate) fn setup_terminal(world: &mut World, window_settings: &CrosstermWindowSettings) -> Entity {
+    let window = {
+        let capabilities = *world.resource::<TerminalCapabilities>();
+        let mut term = world.get_resource_or_insert_with(Terminal::default);
+        enter_terminal(&mut term, window_settings, capabilities)
+    };
+
+    if world.resource::<Terminal>().keyboard_enhanced() {
+        world.resource_mut::<KeyboardState>().reports_releases = true;
+    }
+
+    // Insert our window resources so that other parts of our app can use them
+    let window_id = world.spawn(window).insert(PrimaryWindow).id();
+
+    // Publish to the app that a terminal window has been created
+    {
+        let mut window_created_events = world.get_resource_mut::<Events<WindowCreated>>().unwrap();
+        window_created_events.send(WindowCreated {
+            window: window_id,
+        });
+    }
+
+    window_id
+}
+
+/// Switches the terminal over to the alternate screen in raw mode with the mouse captured, and returns a window
+/// describing it
+fn enter_terminal(
+    term: &mut Terminal,
+    window_settings: &CrosstermWindowSettings,
+    capabilities: TerminalCapabilities,
+) -> CrosstermWindow {
+    let inline = window_settings.inline();
+    if inline.is_none() {
+        term.queue(crossterm::terminal::EnterAlternateScreen)
+            .unwrap();
+        term.queue(crossterm::event::EnableMouseCapture).unwrap();
+    }
+    // Not every terminal can report these, and nothing goes wrong without them
+    let _ = term.queue(crossterm::event::EnableBracketedPaste);
+    let _ = term.queue(crossterm::event::EnableFocusChange);
+
+    term.enable_raw_mode().expect("Could not enable crossterm raw mode");
+
+    // Terminals that can't report key releases are left alone, and `Input<KeyCode>` makes up releases for them instead
+    let enhanced = term.enable_keyboard_enhancement().unwrap_or(false);
+    guard::set_keyboard_enhanced(enhanced);
+
+    let (width, height) = term.size().expect("Could not read current terminal size");
+    let mut window = match inline {
+        None => CrosstermWindow::with_size(width, height),
+        Some(rows) => {
+            // The viewport starts on the line below the cursor, unless the cursor is already at the start of a line.
+            // If there isn't room for it the terminal is scrolled up, pushing what was there into the scrollback
+            let rows = rows.min(height);
+            // Not every terminal answers when asked where the cursor is. Assuming it's on the last line always makes room
+            let (column, row) = term.cursor_position().unwrap_or((0, height.saturating_sub(1)));
+            let start = if column == 0 { row } else { row + 1 };
+            let overflow = (start + rows).saturating_sub(height);
+            if overflow > 0 {
+                term.queue(crossterm::cursor::MoveTo(0, height - 1))
+                    .unwrap()
+                    .queue(crossterm::style::Print("\n".repeat(overflow as usize)))
+                    .unwrap();
+            }
+
+            let mut window = CrosstermWindow::with_size(width, rows);
+            window.origin = start - overflow;
+            window
+        }
+    };
+
+    // Use settings in window
+    if let Some(title) = &window_settings.title() {
+        window.title = Some(title.clone());
+        term.queue(crossterm::terminal::SetTitle(title))
+            .expect("Could not set terminal title");
+    }
+
+    window.colors = window_settings.colors();
+    term.queue(crossterm::style::SetColors(
+        capabilities.downsample(window.colors).to_crossterm(),
+    ))
+        .expect("Could not set window colors");
+
+    if inline.is_some() {
+        term.queue(crossterm::cursor::MoveTo(0, window.origin))
+            .unwrap()
+            .queue(crossterm::terminal::Clear(
+                crossterm::terminal::ClearType::FromCursorDown,
+            ))
+            .expect("Could not clear screen");
+    } else {
+        term.queue(crossterm::terminal::Clear(
+            crossterm::terminal::ClearType::All,
+        ))
+        .expect("Could not clear screen");
+    }
+
+    term.flush().unwrap();
+
+    window
+}
+
+/// Puts the terminal back the way it was before `enter_terminal`. An inline viewport is left on the screen, with the
+/// cursor on a fresh line below it
+fn leave_terminal(world: &mut World, window_settings: &CrosstermWindowSettings) {
+    let last_row = {
+        let window = world
+            .query_filtered::<&CrosstermWindow, With<PrimaryWindow>>()
+            .single(world);
+        (window.origin + window.height).saturating_sub(1)
+    };
+
+    let mut term = world.resource_mut::<Terminal>();
+    // Failing to pop the keyboard flags shouldn't stop the rest of the terminal from being put back
+    if let Err(error) = term.disable_keyboard_enhancement() {
+        warn!("Could not disable keyboard enhancement: {}", error);
+    }
+    guard::set_keyboard_enhanced(false);
+    term.execute(crossterm::event::DisableMouseCapture)
+        .expect("Could not disable mouse capture");
+    let _ = term.queue(crossterm::event::DisableBracketedPaste);
+    let _ = term.queue(crossterm::event::DisableFocusChange);
+    if window_settings.inline().is_some() {
+        term.queue(crossterm::style::SetAttribute(
+            crossterm::style::Attribute::Reset,
+        ))
+        .unwrap()
+        .queue(crossterm::cursor::MoveTo(0, last_row))
+        .unwrap()
+        .queue(crossterm::style::Print("\r\n"))
+        .unwrap();
+    }
+    term.disable_raw_mode().expect("Could not disable raw mode");
+    if window_settings.inline().is_none() {
+        term.execute(crossterm::terminal::LeaveAlternateScreen)
+            .unwrap();
+    }
+    term.execute(crossterm::cursor::Show).unwrap();
+}
+
+/// Hands the terminal back to the shell and stops the process. Once it's continued the terminal is set back up and
+/// everything is redrawn, since whatever ran in the meantime could have drawn over it
+fn suspend_app(app: &mut App, window_id: Entity, window_settings: &CrosstermWindowSettings) {
+    app.world.send_event(Suspended);
+    leave_terminal(&mut app.world, window_settings);
+
+    suspend::stop_process();
+
+    let capabilities = *app.world.resource::<TerminalCapabilities>();
+    let window = enter_terminal(
+        &mut app.world.resource_mut::<Terminal>(),
+        window_settings,
+        capabilities,
+    );
+    app.world.resource_mut::<ScreenBuffer>().full_redraw = true;
+    resize_window(app, window_id, window.width, window.height, window.origin);
+
+    app.world.send_event(Resumed);
+}
+
+/// Moves and resizes the primary window, publishing an event if its size changed. Moving an inline viewport means
+/// everything has to be drawn again in the new spot
+fn resize_window(app: &mut App, window_id: Entity, width: u16, height: u16, origin: u16) {
+    let mut window =
+        app.world.query_filtered::<&mut CrosstermWindow, With<PrimaryWindow>>()
+        .single_mut(&mut app.world);
+    let resized = (window.width, window.height) != (width, height);
+    let moved = window.origin != origin;
+    window.as_mut().height = height;
+    window.as_mut().width = width;
+    window.as_mut().origin = origin;
+
+    if moved {
+        app.world.resource_mut::<ScreenBuffer>().full_redraw = true;
+    }
+
+    if resized {
+        let mut window_resized_events =
+            app.world.get_resource_mut::<Events<WindowResized>>().unwrap();
+        window_resized_events.send(WindowResized {
+            window: window_id,
+            width: width as f32,
+            height: height as f32,
+        });
+    }
+}
+
+/// Records whether the primary window has focus, publishing an event if that changed
+fn focus_window(app: &mut App, window_id: Entity, focused: bool) {
+    let mut window =
+        app.world.query_filtered::<&mut CrosstermWindow, With<PrimaryWindow>>()
+        .single_mut(&mut app.world);
+    if window.focused == focused {
+        return;
+    }
+    window.focused = focused;
+
+    app.world.send_event(WindowFocused {
+        window: window_id,
+        focused,
+    });
 }
@@ -0,0 +1,262 @@
+use bevy::prelude::*;
+
+use crate::components::{GlobalPosition, Position, Sprite, Style, StyleMap, Visible};
+use crate::systems;
+
+// Stands in for the cells a shape doesn't cover. It's the sprite's transparent character, so those cells are never
+// drawn and whatever is underneath shows through
+pub(crate) const EMPTY: char = '\u{0}';
+
+/// The outline of a shape, in cells relative to the entity's position
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ShapeKind {
+    /// A straight line between two points, including both ends
+    Line { from: (i32, i32), to: (i32, i32) },
+    /// A rectangle with its top left corner at the entity's position
+    Rect { width: usize, height: usize, filled: bool },
+    /// An ellipse that fits in a width by height box, with its top left corner at the entity's position
+    Ellipse { width: usize, height: usize, filled: bool },
+    /// Lines joining each point to the next, and the last point back to the first if it's closed
+    Polyline { points: Vec<(i32, i32)>, closed: bool },
+}
+
+/// A shape drawn into the entity's sprite whenever it changes. Every cell the shape covers is drawn with the same
+/// grapheme, which should be one column wide, and the cells it doesn't cover are left see-through.
+///
+/// Like `Text`, the shape's sprite and stylemap belong to it and shouldn't be shared with other entities.
+#[derive(Clone, Debug, Eq, PartialEq, Component)]
+pub struct Shape {
+    pub kind: ShapeKind,
+    pub grapheme: String,
+    pub style: Style,
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Shape::new(ShapeKind::Rect {
+            width: 0,
+            height: 0,
+            filled: false,
+        })
+    }
+}
+
+impl Shape {
+    pub fn new(kind: ShapeKind) -> Shape {
+        Shape {
+            kind,
+            grapheme: "█".to_string(),
+            style: Style::default(),
+        }
+    }
+
+    pub fn line(from: (i32, i32), to: (i32, i32)) -> Shape {
+        Shape::new(ShapeKind::Line { from, to })
+    }
+
+    pub fn rect(width: usize, height: usize) -> Shape {
+        Shape::new(ShapeKind::Rect {
+            width,
+            height,
+            filled: false,
+        })
+    }
+
+    pub fn filled_rect(width: usize, height: usize) -> Shape {
+        Shape::new(ShapeKind::Rect {
+            width,
+            height,
+            filled: true,
+        })
+    }
+
+    pub fn ellipse(width: usize, height: usize) -> Shape {
+        Shape::new(ShapeKind::Ellipse {
+            width,
+            height,
+            filled: false,
+        })
+    }
+
+    pub fn filled_ellipse(width: usize, height: usize) -> Shape {
+        Shape::new(ShapeKind::Ellipse {
+            width,
+            height,
+            filled: true,
+        })
+    }
+
+    pub fn polyline(points: Vec<(i32, i32)>) -> Shape {
+        Shape::new(ShapeKind::Polyline {
+            points,
+            closed: false,
+        })
+    }
+
+    /// A polyline that's closed, so it's the outline of a polygon
+    pub fn polygon(points: Vec<(i32, i32)>) -> Shape {
+        Shape::new(ShapeKind::Polyline {
+            points,
+            closed: true,
+        })
+    }
+
+    pub fn with_grapheme<T: std::string::ToString>(mut self, grapheme: T) -> Shape {
+        self.grapheme = grapheme.to_string();
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Shape {
+        self.style = style;
+        self
+    }
+
+    /// Every cell the shape covers, relative to the entity's position. Cells can be in the list more than once
+    pub fn cells(&self) -> Vec<(i32, i32)> {
+        match &self.kind {
+            ShapeKind::Line { from, to } => line(*from, *to),
+            ShapeKind::Rect {
+                width,
+                height,
+                filled,
+            } => {
+                let (width, height) = (*width as i32, *height as i32);
+                let mut cells = Vec::new();
+                for y in 0..height {
+                    for x in 0..width {
+                        let edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                        if *filled || edge {
+                            cells.push((x, y));
+                        }
+                    }
+                }
+                cells
+            }
+            ShapeKind::Ellipse {
+                width,
+                height,
+                filled,
+            } => ellipse(*width, *height, *filled),
+            ShapeKind::Polyline { points, closed } => {
+                let mut cells: Vec<(i32, i32)> = points.windows(2).flat_map(|pair| line(pair[0], pair[1])).collect();
+                if let (true, Some(first), Some(last)) = (*closed, points.first(), points.last()) {
+                    cells.extend(line(*last, *first));
+                }
+                // A single point is still drawn
+                if let [point] = points.as_slice() {
+                    cells.push(*point);
+                }
+                cells
+            }
+        }
+    }
+
+    /// Draws the shape into a sprite and a stylemap. The sprite is anchored so the shape's 0, 0 is at the entity's
+    /// position, even if some of it is above or to the left of that
+    pub fn layout(&self) -> (Sprite, StyleMap) {
+        let cells = self.cells();
+        let left = cells.iter().map(|(x, _)| *x).min().unwrap_or(0);
+        let top = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let right = cells.iter().map(|(x, _)| *x).max().unwrap_or(-1);
+        let bottom = cells.iter().map(|(_, y)| *y).max().unwrap_or(-1);
+
+        let mut rows = vec![vec![None; (right - left + 1) as usize]; (bottom - top + 1) as usize];
+        for (x, y) in cells {
+            rows[(y - top) as usize][(x - left) as usize] = Some(self.grapheme.as_str());
+        }
+
+        let empty = EMPTY.to_string();
+        let data = rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.unwrap_or(&empty)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let sprite = Sprite::new(data)
+            .with_anchor(-left, -top)
+            .with_transparent(EMPTY);
+
+        (sprite, StyleMap::new(self.style, Vec::new()))
+    }
+}
+
+/// Bresenham's line algorithm
+pub(crate) fn line((x0, y0): (i32, i32), (x1, y1): (i32, i32)) -> Vec<(i32, i32)> {
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let step_x = if x0 < x1 { 1 } else { -1 };
+    let step_y = if y0 < y1 { 1 } else { -1 };
+
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (x0, y0);
+    let mut error = dx + dy;
+    loop {
+        cells.push((x, y));
+        if x == x1 && y == y1 {
+            return cells;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// The cells whose centers are inside the ellipse. The outline is the cells that are inside, next to one that isn't
+fn ellipse(width: usize, height: usize, filled: bool) -> Vec<(i32, i32)> {
+    let (radius_x, radius_y) = (width as f32 / 2.0, height as f32 / 2.0);
+    let inside = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            return false;
+        }
+        let dx = (x as f32 + 0.5 - radius_x) / radius_x;
+        let dy = (y as f32 + 0.5 - radius_y) / radius_y;
+        dx * dx + dy * dy <= 1.0
+    };
+
+    let mut cells = Vec::new();
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            if !inside(x, y) {
+                continue;
+            }
+            let edge = !inside(x - 1, y) || !inside(x + 1, y) || !inside(x, y - 1) || !inside(x, y + 1);
+            if filled || edge {
+                cells.push((x, y));
+            }
+        }
+    }
+    cells
+}
+
+/// An entity that displays a shape. See `Shape`
+#[derive(Bundle, Default)]
+pub struct ShapeBundle {
+    pub shape: Shape,
+    pub sprite: Handle<Sprite>,
+    pub position: Position,
+    pub global_position: GlobalPosition,
+    pub stylemap: Handle<StyleMap>,
+    pub visible: Visible,
+}
+
+pub(crate) fn layout_shapes(
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+    mut shapes: Query<(&Shape, &mut Handle<Sprite>, &mut Handle<StyleMap>), Changed<Shape>>,
+) {
+    for (shape, mut sprite_handle, mut stylemap_handle) in shapes.iter_mut() {
+        let (sprite, stylemap) = shape.layout();
+        systems::replace_assets(
+            (&mut sprites, &mut sprite_handle),
+            (&mut stylemaps, &mut stylemap_handle),
+            sprite,
+            stylemap,
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::guard::SignalHandlers;
+
+/// Sent once the app is running again after being suspended. The terminal has been set back up and the whole screen
+/// is redrawn, but it might have been resized in the meantime
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Resumed;
+
+/// Sent when the app is suspended with the suspend chord or SIGTSTP. Systems see it after the app resumes, alongside
+/// `Resumed`, since nothing runs while the process is stopped
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Suspended;
+
+/// A flag that gets set when the process receives SIGTSTP, so the runner can put the terminal back before stopping
+#[cfg(unix)]
+pub(crate) fn suspend_flag(handlers: &mut SignalHandlers) -> Arc<AtomicBool> {
+    use signal_hook::consts::SIGTSTP;
+
+    let flag = Arc::new(AtomicBool::new(false));
+    handlers.add(SIGTSTP, signal_hook::flag::register(SIGTSTP, flag.clone()));
+    flag
+}
+
+#[cfg(not(unix))]
+pub(crate) fn suspend_flag(_handlers: &mut SignalHandlers) -> Arc<AtomicBool> {
+    Arc::new(AtomicBool::new(false))
+}
+
+/// Stops the process the way SIGTSTP normally would, returning once it's been continued
+#[cfg(unix)]
+pub(crate) fn stop_process() {
+    signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)
+        .expect("Could not stop the process");
+}
+
+/// There's no job control to hand over to, so this carries straight on
+#[cfg(not(unix))]
+pub(crate) fn stop_process() {}
@@ -1,577 +1,258 @@
-use std::{convert::TryInto, io::Write};
+use std::io::Write;
 
-use crate::components::{self, Style};
+use crate::backend::Terminal;
+use crate::buffer::ScreenBuffer;
+use crate::capabilities::TerminalCapabilities;
 use crate::components::{
-    Colors, EntityDepth, Position, PreviousEntityDetails, PreviousPosition, PreviousSize,
-    PreviousWindowColors, Sprite, StyleMap, Visible,
+    grapheme_width, Colors, GlobalPosition, Position, Sprite, Style, StyleMap, Visible,
 };
 use crate::{CrosstermWindow, Cursor};
 
-use bevy::utils::HashSet;
-
 use bevy::prelude::*;
-use bevy::window::{WindowResized, PrimaryWindow};
-use components::EntitiesToRedraw;
-use crossterm::{ExecutableCommand, QueueableCommand};
-
-use broccoli::prelude::*;
-
-pub(crate) fn add_previous_position(
-    mut entities_without_assets: Local<HashSet<Entity>>,
-    mut previous_details: ResMut<PreviousEntityDetails>,
-    frames: Res<Assets<Sprite>>,
-    entities: Query<(Entity, &Position, &Handle<Sprite>), (Added<Position>, Added<Handle<Sprite>>)>,
-    all: Query<(&Position, &Handle<Sprite>)>,
+use bevy::window::PrimaryWindow;
+use crossterm::QueueableCommand;
+
+/// Swaps a new sprite and stylemap into the assets an entity's handles point at, anchor and transparent character
+/// included, or adds them as new assets if the handles don't point at anything yet
+pub(crate) fn replace_assets(
+    (sprites, sprite_handle): (&mut Assets<Sprite>, &mut Handle<Sprite>),
+    (stylemaps, stylemap_handle): (&mut Assets<StyleMap>, &mut Handle<StyleMap>),
+    sprite: Sprite,
+    stylemap: StyleMap,
 ) {
-    for (entity, pos, sprite) in entities.iter() {
-        if let Some(sprite) = frames.get(&*sprite) {
-            let prev_pos = components::PreviousPosition {
-                x: pos.x,
-                y: pos.y,
-                z: pos.z,
-            };
-            let prev_size = components::PreviousSize {
-                width: sprite.width() as u16,
-                height: sprite.graphemes().len() as u16,
-            };
-            previous_details.0.insert(entity, (prev_pos, prev_size));
-        } else {
-            // The asset hasn't loaded yet, so let's make a record of it for later
-            entities_without_assets.insert(entity);
-        }
+    match sprites.get_mut(sprite_handle) {
+        Some(existing) => *existing = sprite,
+        None => *sprite_handle = sprites.add(sprite),
     }
-
-    // Bevy loads assets asynchronously so if we couldn't find an asset, we add it to this list and
-    // try again until it loads
-    let mut entities_to_remove = Vec::new();
-    for entity in entities_without_assets.iter() {
-        let data = all.get(*entity);
-        if data.is_err() {
-            continue;
-        }
-        let (pos, sprite) = data.unwrap();
-
-        if let Some(sprite) = frames.get(&*sprite) {
-            let prev_pos = PreviousPosition {
-                x: pos.x,
-                y: pos.y,
-                z: pos.z,
-            };
-            let prev_size = PreviousSize {
-                width: sprite.width() as u16,
-                height: sprite.graphemes().len() as u16,
-            };
-            previous_details.0.insert(*entity, (prev_pos, prev_size));
-
-            // We need to remove this entity now, but can't since it's container is borrowed.
-            {
-                // Needs it's own scope or the borrow checker can't tell when thee mutable borrow ends
-                entities_to_remove.push(*entity);
-            }
-        }
+    match stylemaps.get_mut(stylemap_handle) {
+        Some(existing) => *existing = stylemap,
+        None => *stylemap_handle = stylemaps.add(stylemap),
     }
+}
 
-    for entity in entities_to_remove.iter() {
-        entities_without_assets.remove(entity);
+/// Gives entities that were spawned with a `Position` but no `GlobalPosition` one, so they're still drawn and picked
+pub(crate) fn insert_global_positions(
+    mut commands: Commands,
+    added: Query<Entity, (Added<Position>, Without<GlobalPosition>)>,
+) {
+    for entity in added.iter() {
+        commands.entity(entity).insert(GlobalPosition::default());
     }
 }
 
-pub(crate) fn update_previous_position(
-    mut previous_details: ResMut<PreviousEntityDetails>,
-    frames: Res<Assets<Sprite>>,
-    mut positions: Query<(Entity, &Position, &Handle<Sprite>, &Visible)>,
+/// Works out where every entity really is by adding up its position with those of its ancestors
+pub(crate) fn propagate_positions(
+    mut globals: Query<(Entity, &mut GlobalPosition)>,
+    positions: Query<(&Position, Option<&Parent>)>,
 ) {
-    for (entity, new_pos, sprite, _) in positions.iter_mut() {
-        if let Some(sprite) = frames.get(sprite) {
-            let prev_pos = PreviousPosition {
-                x: new_pos.x,
-                y: new_pos.y,
-                z: new_pos.z,
-            };
-            let prev_size = PreviousSize {
-                width: sprite.width() as u16,
-                height: sprite.graphemes().len() as u16,
-            };
-
-            if let Some(value) = previous_details.0.get_mut(&entity) {
-                *value = (prev_pos, prev_size);
-            }
+    for (entity, mut global) in globals.iter_mut() {
+        let mut new_global = GlobalPosition::default();
+
+        // Walk up the hierarchy until we run out of parents that have a position
+        let mut current = Some(entity);
+        while let Some((pos, parent)) = current.and_then(|entity| positions.get(entity).ok()) {
+            new_global.x += pos.x;
+            new_global.y += pos.y;
+            new_global.z += pos.z;
+            current = parent.map(|parent| parent.get());
+        }
+
+        // Only touch the component if it's different, so change detection stays useful
+        if *global != new_global {
+            *global = new_global;
         }
     }
 }
 
-pub(crate) fn calculate_entities_to_redraw(
-    mut prev_colors: ResMut<PreviousWindowColors>,
-    mut entities: ResMut<EntitiesToRedraw>,
-    previous_details: Res<PreviousEntityDetails>,
+/// Builds this frame's picture of the screen in the back buffer, out of every visible sprite
+pub(crate) fn composite(
+    mut buffer: ResMut<ScreenBuffer>,
     window: Query<&CrosstermWindow, With<PrimaryWindow>>,
-    resize_events: Res<Events<WindowResized>>,
     sprites: Res<Assets<Sprite>>,
-    sprite_asset_events: Res<Events<AssetEvent<Sprite>>>,
-    stylemap_asset_events: Res<Events<AssetEvent<StyleMap>>>,
+    stylemaps: Res<Assets<StyleMap>>,
     all: Query<(
         Entity,
+        &GlobalPosition,
         &Handle<StyleMap>,
-        &Handle<Sprite>,
-        &Position,
         &Visible,
+        &Handle<Sprite>,
     )>,
-    changed: Query<
-        Entity,
-        Or<(
-            Changed<Position>,
-            Changed<Handle<StyleMap>>,
-            Changed<Visible>,
-            Changed<Handle<Sprite>>,
-        )>,
-    >,
-    added: Query<
-        Entity,
-        (
-            Or<(
-                Added<Position>,
-                Added<Handle<StyleMap>>,
-                Added<Visible>,
-                Added<Handle<Sprite>>,
-            )>,
-            With<Position>,
-            With<Handle<StyleMap>>,
-            With<Visible>,
-            With<Handle<Sprite>>,
-        ),
-        >,
-    mut removed: RemovedComponents<Handle<Sprite>>,
 ) {
     let window = window.single();
-
-    entities.full_redraw = false;
-    entities.to_draw.clear();
-    entities.to_clear.clear();
-
-    let mut draw_set = HashSet::default();
-
-    // If a resize happened the whole screen is invalidated
-    if resize_events.get_reader().iter(&resize_events).last().is_some() || window.colors != prev_colors.0
-    {
-        // We need a full redraw, so flag a full update and bail early
-        // No need to do fancy update calculations
-        entities.full_redraw = true;
-        prev_colors.0 = window.colors;
-        // Mark all entities as needed to redraw
-        for (entity, _, _, pos, _) in all.iter() {
-            entities.to_draw.push(EntityDepth { entity, z: pos.z });
+    let window_colors = window.colors.with_default(Colors::term_colors());
+
+    buffer.resize(window.width, window.height);
+    buffer.clear(window_colors);
+
+    // Paint from the bottom up so that higher sprites cover lower ones. Ties are broken by entity so the result is
+    // the same every frame
+    let mut to_draw: Vec<_> = all
+        .iter()
+        .filter(|(_, _, _, visible, _)| visible.is_visible)
+        .collect();
+    to_draw.sort_by_key(|(entity, pos, _, _, _)| (pos.z, *entity));
+
+    for (_, pos, stylemap, visible, sprite) in to_draw {
+        // Assets load asynchronously, anything that hasn't loaded yet will be drawn once it has
+        if let (Some(sprite), Some(stylemap)) = (sprites.get(sprite), stylemaps.get(stylemap)) {
+            draw_sprite(&mut buffer, window_colors, pos, sprite, stylemap, visible);
         }
-        entities.to_draw.sort_by_key(|item| item.z);
-        return;
     }
+}
 
-    // Now check to see which entities actually changed since it's not a full update
-
-    // Collect all the changed assets
-    let mut created_sprite_assets = bevy::utils::HashSet::default();
-    let mut changed_sprite_assets = bevy::utils::HashSet::default();
-    let mut created_stylemap_assets = bevy::utils::HashSet::default();
-    let mut changed_stylemap_assets = bevy::utils::HashSet::default();
-    for evt in sprite_asset_events.get_reader().iter(&sprite_asset_events) {
-        match &*evt {
-            AssetEvent::Created { handle } => {
-                created_sprite_assets.insert(handle.clone());
-            }
-            AssetEvent::Modified { handle } => {
-                changed_sprite_assets.insert(handle.clone());
-            }
-            _ => {}
-        }
-    }
-    for evt in stylemap_asset_events
-        .get_reader()
-        .iter(&stylemap_asset_events)
-    {
-        match &*evt {
-            AssetEvent::Created { handle } => {
-                created_stylemap_assets.insert(handle.clone());
-            }
-            AssetEvent::Modified { handle } => {
-                changed_stylemap_assets.insert(handle.clone());
-            }
-            _ => {}
-        }
-    }
-
-    // Collect all the entities that changed this update, either because their asset did,
-    // or their components did
-    for (entity, style_hnd, sprite_hnd, _, _) in all.iter() {
-        if changed_sprite_assets.contains(sprite_hnd) || changed_stylemap_assets.contains(style_hnd)
-        {
-            entities.to_clear.insert(entity);
-            draw_set.insert(entity);
-        }
-
-        if created_sprite_assets.contains(sprite_hnd) || created_stylemap_assets.contains(style_hnd)
-        {
-            draw_set.insert(entity);
-        }
-    }
+fn draw_sprite(
+    buffer: &mut ScreenBuffer,
+    window_colors: Colors,
+    pos: &GlobalPosition,
+    sprite: &Sprite,
+    stylemap: &StyleMap,
+    visible: &Visible,
+) {
+    let sprite_style = Style::new(
+        stylemap.style.colors.with_default(window_colors),
+        stylemap.style.attributes,
+    );
+
+    let resolve = |style: Option<&Style>| match style {
+        Some(style) => Style::new(
+            style.colors.with_default(sprite_style.colors),
+            style.attributes,
+        ),
+        None => sprite_style,
+    };
 
-    for entity in changed.iter() {
-        entities.to_clear.insert(entity);
-        draw_set.insert(entity);
-    }
+    let (anchor_x, anchor_y) = sprite.anchor();
+    let pos = GlobalPosition {
+        x: pos.x - anchor_x,
+        y: pos.y - anchor_y,
+        z: pos.z,
+    };
 
-    for entity in added.iter() {
-        draw_set.insert(entity);
-    }
+    for line_num in 0..sprite.height() {
+        let y = pos.y + line_num as i32;
 
-    // Find all entities that either became invisible, or changed their size or moved. (cleared is good enough for now)
-    // Figure out what their previous bounding box is and query all current positions to see what sprites are under it
-    // Add the collided entities to draw_set
-    let mut new_ents = Vec::new();
-    let mut bboxes = Vec::new();
-    for (entity, _, sprite, pos, _) in all.iter() {
-        let sprite_data = sprites.get(sprite);
-        if sprite_data.is_none() {
+        if y < 0 {
             continue;
         }
-        let sprite = sprite_data.unwrap();
-        let bb = broccoli::bbox(
-            broccoli::rect(
-                pos.x,
-                pos.x + sprite.width() as i32,
-                pos.y,
-                pos.y + sprite.data().len() as i32,
-            ),
-            entity,
-        );
-        bboxes.push(bb);
-    }
 
-    let broccoli = broccoli::new(&mut bboxes);
-    for ent in changed.iter() {
-        let prev_data = previous_details.0.get(&ent);
-        if prev_data.is_none() {
-            continue;
+        // If this line is off the bottom of the screen, no lines after it can be on the screen either
+        if y >= buffer.height() as i32 {
+            break;
         }
-        let (prev_pos, prev_size) = prev_data.unwrap();
-        let blank_bb = broccoli::rect(
-            prev_pos.x,
-            prev_pos.x + prev_size.width as i32,
-            prev_pos.y,
-            prev_pos.y + prev_size.height as i32,
-        );
-        // dbg!("checking for collision", ent, prev_pos);
-        broccoli.for_all_intersect_rect(&blank_bb, |bb| {
-            if ent == bb.inner {
-                return;
-            }
-            // dbg!("Found Entity: ", bb.inner);
-            if !draw_set.contains(&bb.inner) {
-                draw_set.insert(bb.inner);
-                new_ents.push(bb.inner);
-            }
-        });
-    }
 
-    let mut cur_index = 0;
-    while cur_index < new_ents.len() {
-        let ent = new_ents[cur_index];
-        cur_index += 1;
+        let mut line_width = 0;
+        for (column, grapheme) in sprite.columns(line_num) {
+            let width = grapheme_width(grapheme) as i32;
+            let x = pos.x + column as i32;
+            line_width = column + width as usize;
 
-        let prev_data = previous_details.0.get(&ent);
-        if prev_data.is_none() {
-            continue;
-        }
-        let (prev_pos, prev_size) = prev_data.unwrap();
-        let blank_bb = broccoli::rect(
-            prev_pos.x,
-            prev_pos.x + prev_size.width as i32,
-            prev_pos.y,
-            prev_pos.y + prev_size.height as i32,
-        );
-        // dbg!("checking for collision", ent, prev_pos);
-        broccoli.for_all_intersect_rect(&blank_bb, |bb| {
-            if ent == bb.inner {
-                return;
+            if x + width <= 0 {
+                continue;
             }
-            // dbg!("Found Entity: ", bb.inner);
-            if !draw_set.contains(&bb.inner) {
-                draw_set.insert(bb.inner);
-                new_ents.push(bb.inner);
+            if x >= buffer.width() as i32 {
+                break;
             }
-        });
-    }
 
-    for entity in removed.iter() {
-        entities.to_clear.insert(entity);
-    }
-
-    for ent_to_draw in draw_set.iter() {
-        let (entity, _, _, pos, _) = all.get(*ent_to_draw).unwrap();
-        entities
-            .to_draw
-            .push(components::EntityDepth { entity, z: pos.z });
-    }
-    entities.to_draw.sort_by_key(|item| item.z);
-}
-
-/// Helper function for draw_entity which determines whether the style on the terminal should be
-/// changed
-fn change_style_if_needed(
-    term: &mut std::io::StdoutLock,
-    previous_style: &mut Style,
-    current_style: &Style,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if current_style.attributes != previous_style.attributes {
-        term.queue(crossterm::style::SetAttributes(current_style.attributes))?;
-        previous_style.attributes = current_style.attributes;
-    }
-    if current_style.colors != previous_style.colors {
-        term.queue(crossterm::style::SetColors(
-            current_style.colors.to_crossterm(),
-        ))?;
-        previous_style.colors = current_style.colors;
-    }
-    Ok(())
-}
-
-fn draw_entity(
-    entity: Entity,
-    term: &mut std::io::StdoutLock,
-    window: &CrosstermWindow,
-    sprites: &Res<Assets<Sprite>>,
-    stylemaps: &Res<Assets<StyleMap>>,
-    all: &Query<(
-        Entity,
-        &Position,
-        &Handle<StyleMap>,
-        &components::Visible,
-        &Handle<Sprite>,
-    )>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let entity_data = all.get(entity);
-    if entity_data.is_err() {
-        return Ok(());
-    }
-    let (_, pos, style, draw, sprite) = entity_data.unwrap();
-
-    // If the entity isn't visible, skip it
-    if !draw.is_visible {
-        return Ok(());
-    }
-
-    let sprite = sprites.get(&*sprite);
-    if sprite.is_none() {
-        // The sprite asset hasn't loaded yet, this isn't a problem
-        return Ok(());
-    }
-    let sprite = sprite.unwrap();
-
-    // If the entity's not on the screen, skip it
-    if pos.y >= window.height.into()
-        || pos.y + sprite.height() as i32 <= 0
-        || pos.x >= window.width.into()
-        || pos.x + sprite.width() as i32 <= 0
-    {
-        return Ok(());
-    }
-
-    let stylemap = stylemaps.get(&*style);
-    if stylemap.is_none() {
-        // The stylemap asset hasn't loaded yet, this isn't a problem
-        return Ok(());
-    }
-    let stylemap = stylemap.unwrap();
-    let sprite_colors = stylemap.style.colors.with_default(window.colors);
-
-    term.queue(crossterm::style::SetAttribute(
-        crossterm::style::Attribute::Reset,
-    ))?
-    .queue(crossterm::style::SetAttributes(stylemap.style.attributes))?
-    .queue(crossterm::style::SetColors(sprite_colors.to_crossterm()))?;
-
-    let mut previous_style = stylemap.style;
-
-    for (line_num, line) in sprite.graphemes().iter().enumerate() {
-        let line_offset: i32 = line_num.try_into()?;
-
-        // Check to see if this line is on the screen, if not skip it
-        if pos.y + line_offset < 0 {
-            continue;
-        }
-
-        // If this line is off the bottom of the screen, break out since no lines can ever
-        // be on the screen ever again
-        if pos.y + line_offset >= window.height.into() {
-            break;
-        }
-
-        // Calculate the beginning and end of string sprte, to not render things off screen
-        let start: i32 = std::cmp::max(0, pos.x);
-        let end: i32 = std::cmp::min(window.width as i32, pos.x + line.len() as i32);
-
-        let start_idx: usize = (start - pos.x).try_into()?;
-        let end_idx: usize = (end - pos.x).try_into()?;
-
-        term.queue(crossterm::cursor::MoveTo(
-            start.try_into()?,
-            (pos.y + line_offset).try_into()?,
-        ))?;
-
-        let graphemes = &line[start_idx..end_idx];
-        if !graphemes.is_empty() {
-            // Go through each grapheme one by one to make sure we have the correct style and color
-            // (Cross reference with the stylemap, otherwise default to )
-            for (i, grapheme) in graphemes.iter().enumerate() {
-                let idx = start_idx + i;
-
-                // If the grapheme is a transparent space with no style, skip rendering it
-                if draw.is_transparent
-                    && stylemap.style_at(idx, line_num).is_none()
-                    && sprite.grapheme(grapheme) == " "
-                {
-                    term.queue(crossterm::cursor::MoveRight(1))?;
-                    continue;
-                }
+            if sprite.is_transparent_grapheme(grapheme) {
+                continue;
+            }
 
-                // Get the style we need to render this grapheme with
-                let grapheme_style = stylemap.style_for(idx, line_num);
-                change_style_if_needed(term, &mut previous_style, &grapheme_style)?;
+            let style = stylemap.style_at(column, line_num);
 
-                term.queue(crossterm::style::Print(&sprite.grapheme(grapheme)))?;
+            // Transparent sprites let whatever is underneath show through their unstyled spaces
+            if visible.is_transparent && style.is_none() && grapheme == " " {
+                continue;
             }
-        }
 
-        // Lines don't have to go to the end of the sprite. Pad them out so the sprite is rectangular
-        if end < window.width as i32 && line.len() < sprite.width() {
-            let unaccounted = sprite.width() - line.len();
-            let blank_length = std::cmp::min(unaccounted, (window.width as i32 - end) as usize);
-            let blank_str = str::repeat(" ", blank_length);
-            for (i, space) in blank_str.chars().enumerate() {
-                let idx = end_idx + i;
-
-                // If the filler space is transparent and has no style, skip it
-                if draw.is_transparent && stylemap.style_at(idx, line_num).is_none() {
-                    term.queue(crossterm::cursor::MoveRight(1))?;
-                    continue;
+            let style = resolve(style);
+            if x < 0 || x + width > buffer.width() as i32 {
+                // A wide grapheme hanging off the edge of the screen can't be drawn in half, so the part of it that's
+                // on the screen is blanked instead
+                for offset in 0..width {
+                    buffer.set(x + offset, y, " ", &style);
                 }
-
-                // Get the style we need to render this space with
-                let grapheme_style = stylemap.style_for(idx, line_num);
-                change_style_if_needed(term, &mut previous_style, &grapheme_style)?;
-
-                term.queue(crossterm::style::Print(space))?;
+            } else {
+                buffer.set(x, y, grapheme, &style);
             }
         }
-    }
 
-    Ok(())
-}
+        // Lines don't have to go to the end of the sprite. Pad them out with spaces so the sprite is rectangular
+        let first_column = std::cmp::max(line_width as i32, -pos.x);
+        let last_column = std::cmp::min(sprite.width() as i32, buffer.width() as i32 - pos.x);
+        for column in first_column..last_column {
+            let style = stylemap.style_at(column as usize, line_num);
 
-fn clear_entity(
-    entity: Entity,
-    term: &mut std::io::StdoutLock,
-    window: &CrosstermWindow,
-    previous_details: &PreviousEntityDetails,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let prev_details = previous_details.0.get(&entity);
-    if prev_details.is_none() {
-        // We didn't have a chance to create previous details for this entity yet
-        // Since it's sprite is most likely still loading
-        return Ok(());
-    }
-    let (prev_pos, prev_size) = prev_details.unwrap();
-
-    for height in 0..prev_size.height {
-        let y = prev_pos.y + height as i32;
-
-        if y < 0 {
-            continue;
-        }
+            if visible.is_transparent && style.is_none() {
+                continue;
+            }
 
-        if prev_pos.y >= window.height.into()
-            || prev_pos.y + prev_size.height as i32 <= 0
-            || prev_pos.x >= window.width.into()
-            || prev_pos.x + prev_size.width as i32 <= 0
-        {
-            break;
+            buffer.set(pos.x + column, y, " ", &resolve(style));
         }
-
-        let x_start: i32 = std::cmp::max(0, prev_pos.x);
-        let x_end: i32 = std::cmp::min(window.width as i32, prev_pos.x + prev_size.width as i32);
-
-        let actual_width = x_end - x_start;
-        let blank_string = " ".repeat(actual_width as usize);
-
-        term.queue(crossterm::style::SetAttribute(
-            crossterm::style::Attribute::Reset,
-        ))?
-        .queue(crossterm::style::SetColors(
-            Colors::term_colors().to_crossterm(),
-        ))?
-        .queue(crossterm::cursor::MoveTo(
-            x_start.try_into()?,
-            y.try_into()?,
-        ))?
-        .queue(crossterm::style::Print(blank_string))?;
     }
-
-    Ok(())
 }
 
-
+/// Sends everything that changed since the last frame to the terminal
 pub(crate) fn crossterm_render(
-    changed_entities: Res<EntitiesToRedraw>,
+    mut term: ResMut<Terminal>,
+    mut buffer: ResMut<ScreenBuffer>,
     window: Query<&CrosstermWindow, With<PrimaryWindow>>,
     cursor: Res<Cursor>,
-    previous_details: Res<PreviousEntityDetails>,
-    sprites: Res<Assets<Sprite>>,
-    stylemaps: Res<Assets<StyleMap>>,
-    all: Query<(
-        Entity,
-        &Position,
-        &Handle<StyleMap>,
-        &Visible,
-        &Handle<Sprite>,
-    )>,
+    capabilities: Res<TerminalCapabilities>,
 ) {
-    let stdout = std::io::stdout();
-    let mut term = stdout.lock();
+    let term = &mut *term;
     let window = window.single();
 
+    let mut changes = buffer.changes().peekable();
+
     // If we're gonna be drawing stuff, hide the cursor so it doesn't jump all over the place
-    if !changed_entities.to_draw.is_empty() {
-        term.execute(crossterm::cursor::Hide).unwrap();
+    if changes.peek().is_some() {
+        term.queue(crossterm::cursor::Hide).unwrap();
     }
 
-    // If a resize happened, clear the screen and go from there
-    if changed_entities.full_redraw {
-        term.execute(crossterm::style::SetAttribute(
-            crossterm::style::Attribute::Reset,
-        ))
-        .unwrap()
-        .execute(crossterm::terminal::Clear(
-            crossterm::terminal::ClearType::All,
-        ))
-        .unwrap();
-    }
+    // Track what the terminal's cursor and pen are doing, so we only move or restyle when we have to
+    let mut term_cursor = None;
+    let mut pen: Option<(crossterm::style::Attributes, Colors)> = None;
+    for (x, y, cell) in changes {
+        // The second half of a wide grapheme was taken care of when the first half was printed
+        if cell.grapheme.is_empty() {
+            continue;
+        }
 
-    // Blank out all the previous locations of sprites that changed either their position or their size
-    for entity in changed_entities.to_clear.iter() {
-        clear_entity(*entity, &mut term, &window, &previous_details).unwrap();
-    }
+        if term_cursor != Some((x, y)) {
+            term.queue(crossterm::cursor::MoveTo(x, y + window.origin)).unwrap();
+        }
 
-    // Redraw all the changed sprites, either because they moved, or because they changed their shape
-    for entity in changed_entities.to_draw.iter() {
-        draw_entity(
-            entity.entity,
-            &mut term,
-            &window,
-            &sprites,
-            &stylemaps,
-            &all,
-        )
-        .unwrap();
+        match pen {
+            Some((attributes, colors)) if attributes == cell.attributes => {
+                if colors != cell.colors {
+                    term.queue(crossterm::style::SetColors(
+                        capabilities.downsample(cell.colors).to_crossterm(),
+                    ))
+                    .unwrap();
+                }
+            }
+            // Attributes can only be turned off by a reset, which also resets the colors
+            _ => {
+                term.queue(crossterm::style::SetAttribute(
+                    crossterm::style::Attribute::Reset,
+                ))
+                .unwrap()
+                .queue(crossterm::style::SetAttributes(cell.attributes))
+                .unwrap()
+                .queue(crossterm::style::SetColors(
+                    capabilities.downsample(cell.colors).to_crossterm(),
+                ))
+                .unwrap();
+            }
+        }
+        pen = Some((cell.attributes, cell.colors));
+
+        term.queue(crossterm::style::Print(&cell.grapheme)).unwrap();
+        term_cursor = Some((x + grapheme_width(&cell.grapheme) as u16, y));
     }
 
+    buffer.swap();
+
     // Draw the cursor at the right position, if needed
     if !cursor.hidden {
         if cursor.x >= 0
@@ -579,7 +260,10 @@ pub(crate) fn crossterm_render(
             && cursor.y >= 0
             && cursor.y < window.height as i32
         {
-            term.queue(crossterm::cursor::MoveTo(cursor.x as u16, cursor.y as u16))
+            term.queue(crossterm::cursor::MoveTo(
+                cursor.x as u16,
+                cursor.y as u16 + window.origin,
+            ))
                 .unwrap();
             term.queue(crossterm::cursor::Show).unwrap();
         }
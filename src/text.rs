@@ -0,0 +1,325 @@
+use bevy::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::components::{grapheme_width, GlobalPosition, Position, Sprite, Style, StyleMap, Visible};
+use crate::systems;
+
+/// A run of text that's all drawn in the same style
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TextSection {
+    pub value: String,
+    pub style: Style,
+}
+
+impl TextSection {
+    pub fn new<T: std::string::ToString>(value: T, style: Style) -> TextSection {
+        TextSection {
+            value: value.to_string(),
+            style,
+        }
+    }
+}
+
+/// Where each line goes within the text's width
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// What happens to lines that are wider than the text's max width
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TextOverflow {
+    /// Break lines between words. Words that don't fit on a line by themselves are broken wherever they need to be
+    #[default]
+    Wrap,
+    /// Cut lines off at the max width
+    Clip,
+    /// Cut lines off at the max width, with a `…` to show something is missing
+    Ellipsis,
+}
+
+/// Styled text that's laid out into the entity's sprite and stylemap whenever it changes, so the text can be changed
+/// by editing the sections instead of building a sprite and stylemap by hand. Newlines in the sections start a new
+/// line.
+///
+/// The sprite and stylemap belong to the text, so they shouldn't be shared with other entities. If the entity's handles
+/// don't point at anything yet, new assets are added for them.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Component)]
+pub struct Text {
+    pub sections: Vec<TextSection>,
+    pub alignment: TextAlignment,
+    /// How many columns wide the text can be. If there is a max width, lines are aligned within it, otherwise they're
+    /// aligned within the widest line
+    pub max_width: Option<usize>,
+    pub overflow: TextOverflow,
+}
+
+impl Text {
+    pub fn from_section<T: std::string::ToString>(value: T, style: Style) -> Text {
+        Text::from_sections(vec![TextSection::new(value, style)])
+    }
+
+    pub fn from_sections(sections: Vec<TextSection>) -> Text {
+        Text {
+            sections,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Text {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: usize) -> Text {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: TextOverflow) -> Text {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Lays the text out into a sprite and a stylemap that lines up with it. This is done automatically for entities
+    /// with a `Text`, but it's handy for finding out how big some text will be
+    pub fn layout(&self) -> (Sprite, StyleMap) {
+        let mut lines = Vec::new();
+        for paragraph in self.paragraphs() {
+            match (self.max_width, self.overflow) {
+                (None, _) => lines.push(paragraph),
+                (Some(max_width), TextOverflow::Wrap) => lines.extend(wrap(paragraph, max_width)),
+                (Some(max_width), TextOverflow::Clip) => lines.push(clip(paragraph, max_width, false)),
+                (Some(max_width), TextOverflow::Ellipsis) => lines.push(clip(paragraph, max_width, true)),
+            }
+        }
+
+        let widest = lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+        let width = self.max_width.unwrap_or(widest);
+
+        let mut data = String::new();
+        let mut map = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                data.push('\n');
+            }
+
+            let padding = match self.alignment {
+                TextAlignment::Left => 0,
+                TextAlignment::Center => width.saturating_sub(line_width(line)) / 2,
+                TextAlignment::Right => width.saturating_sub(line_width(line)),
+            };
+            let mut styles = vec![Style::default(); padding];
+            data.push_str(&" ".repeat(padding));
+            for (grapheme, style) in line {
+                data.push_str(grapheme);
+                // The map is indexed by column, so a wide grapheme has a style for both of its columns
+                styles.extend(std::iter::repeat_n(*style, grapheme_width(grapheme)));
+            }
+            map.push(styles);
+        }
+
+        (Sprite::new(data), StyleMap::new(Style::default(), map))
+    }
+
+    /// Every grapheme in the text with its style, split up into lines at newlines
+    fn paragraphs(&self) -> Vec<Vec<(&str, Style)>> {
+        let mut paragraphs = vec![Vec::new()];
+        for section in &self.sections {
+            for grapheme in section.value.graphemes(true) {
+                match grapheme {
+                    "\n" | "\r\n" => paragraphs.push(Vec::new()),
+                    _ => paragraphs.last_mut().unwrap().push((grapheme, section.style)),
+                }
+            }
+        }
+        paragraphs
+    }
+}
+
+fn line_width(line: &[(&str, Style)]) -> usize {
+    line.iter().map(|(grapheme, _)| grapheme_width(grapheme)).sum()
+}
+
+fn is_space(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+/// Breaks a line up so none of the lines are wider than max_width. Lines are broken at the spaces between words, and
+/// the spaces a line is broken at are left out
+fn wrap(paragraph: Vec<(&str, Style)>, max_width: usize) -> Vec<Vec<(&str, Style)>> {
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    let mut width = 0;
+
+    // Runs of spaces and words, each as a list of graphemes
+    let mut pieces: Vec<Vec<(&str, Style)>> = Vec::new();
+    for (grapheme, style) in paragraph {
+        match pieces.last_mut() {
+            Some(piece) if is_space(piece[0].0) == is_space(grapheme) => piece.push((grapheme, style)),
+            _ => pieces.push(vec![(grapheme, style)]),
+        }
+    }
+
+    // Spaces are held back until the next word, since they're dropped if the line breaks there
+    let mut spaces = Vec::new();
+    for piece in pieces {
+        if is_space(piece[0].0) {
+            spaces = piece;
+            continue;
+        }
+
+        let word_width = line_width(&piece);
+        let spaces_width = line_width(&spaces);
+        if width + spaces_width + word_width <= max_width {
+            width += spaces_width + word_width;
+            line.append(&mut spaces);
+            line.extend(piece);
+            continue;
+        }
+
+        spaces.clear();
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            width = 0;
+        }
+
+        // A word that's too long for a line of its own is split up wherever it has to be
+        for (grapheme, style) in piece {
+            let columns = grapheme_width(grapheme);
+            if width + columns > max_width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+                width = 0;
+            }
+            line.push((grapheme, style));
+            width += columns;
+        }
+    }
+
+    // Spaces at the end of the text are kept as long as they fit
+    if width + line_width(&spaces) <= max_width {
+        line.append(&mut spaces);
+    }
+    lines.push(line);
+    lines
+}
+
+/// Cuts a line off at max_width, ending it with an ellipsis in the style of the last grapheme kept if asked to
+fn clip(mut line: Vec<(&str, Style)>, max_width: usize, ellipsis: bool) -> Vec<(&str, Style)> {
+    if line_width(&line) <= max_width {
+        return line;
+    }
+
+    let room = if ellipsis { max_width.saturating_sub(1) } else { max_width };
+    let mut width = 0;
+    let kept = line
+        .iter()
+        .take_while(|(grapheme, _)| {
+            width += grapheme_width(grapheme);
+            width <= room
+        })
+        .count();
+    let style = line[..kept.max(1)].last().map(|(_, style)| *style).unwrap();
+    line.truncate(kept);
+    if ellipsis && max_width > 0 {
+        line.push(("…", style));
+    }
+    line
+}
+
+/// An entity that displays text. See `Text`
+#[derive(Bundle, Default)]
+pub struct TextBundle {
+    pub text: Text,
+    pub sprite: Handle<Sprite>,
+    pub position: Position,
+    pub global_position: GlobalPosition,
+    pub stylemap: Handle<StyleMap>,
+    pub visible: Visible,
+}
+
+pub(crate) fn layout_text(
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+    mut texts: Query<(&Text, &mut Handle<Sprite>, &mut Handle<StyleMap>), Changed<Text>>,
+) {
+    for (text, mut sprite_handle, mut stylemap_handle) in texts.iter_mut() {
+        let (sprite, stylemap) = text.layout();
+        systems::replace_assets(
+            (&mut sprites, &mut sprite_handle),
+            (&mut stylemaps, &mut stylemap_handle),
+            sprite,
+            stylemap,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphemes(text: &str) -> Vec<(&str, Style)> {
+        text.graphemes(true).map(|grapheme| (grapheme, Style::default())).collect()
+    }
+
+    fn joined(line: &[(&str, Style)]) -> String {
+        line.iter().map(|(grapheme, _)| *grapheme).collect()
+    }
+
+    fn wrapped(text: &str, max_width: usize) -> Vec<String> {
+        wrap(graphemes(text), max_width).iter().map(|line| joined(line)).collect()
+    }
+
+    fn clipped(text: &str, max_width: usize, ellipsis: bool) -> String {
+        joined(&clip(graphemes(text), max_width, ellipsis))
+    }
+
+    #[test]
+    fn wrap_breaks_between_words() {
+        assert_eq!(wrapped("the quick brown fox", 10), ["the quick", "brown fox"]);
+        assert_eq!(wrapped("the quick brown fox", 19), ["the quick brown fox"]);
+        assert_eq!(wrapped("ab   cd", 2), ["ab", "cd"]);
+        assert_eq!(wrapped("", 5), [""]);
+    }
+
+    #[test]
+    fn wrap_splits_words_that_are_too_long() {
+        assert_eq!(wrapped("abcdefghijklmno", 6), ["abcdef", "ghijkl", "mno"]);
+        assert_eq!(wrapped("hi abcdefghij", 6), ["hi", "abcdef", "ghij"]);
+        assert_eq!(wrapped("漢字漢字", 5), ["漢字", "漢字"]);
+    }
+
+    #[test]
+    fn wrap_keeps_trailing_spaces_that_fit() {
+        assert_eq!(wrapped("ab  ", 4), ["ab  "]);
+        assert_eq!(wrapped("ab  ", 3), ["ab"]);
+        assert_eq!(wrapped("  ab", 4), ["  ab"]);
+    }
+
+    #[test]
+    fn clip_cuts_lines_off() {
+        assert_eq!(clipped("hello world", 5, false), "hello");
+        assert_eq!(clipped("hello", 5, false), "hello");
+        assert_eq!(clipped("hello", 5, true), "hello");
+        assert_eq!(clipped("hello world", 5, true), "hell…");
+        assert_eq!(clipped("hello", 0, true), "");
+    }
+
+    #[test]
+    fn ellipsis_with_wide_graphemes() {
+        // A wide grapheme that would only half fit is dropped, so the line can end up narrower than max_width
+        assert_eq!(clipped("漢字漢字", 5, true), "漢字…");
+        assert_eq!(clipped("漢字漢字", 4, true), "漢…");
+        assert_eq!(clipped("漢字漢字", 3, false), "漢");
+    }
+
+    #[test]
+    fn ellipsis_has_the_style_of_the_last_grapheme_kept() {
+        let red = Style::with_fg(crossterm::style::Color::Red);
+        let line = vec![("a", red), ("b", red), ("c", Style::default()), ("d", Style::default())];
+        assert_eq!(clip(line, 3, true), vec![("a", red), ("b", red), ("…", red)]);
+    }
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use bevy_crossterm::prelude::{Color, MouseButton, *};
+
+// These cover the same cases the transparency and redraw_demo examples show off, against a virtual screen
+
+#[derive(Component)]
+struct Tag;
+
+// An app that draws into a virtual screen, with its startup systems run and the first frame drawn
+fn headless_app<M>(width: u16, height: u16, startup_system: impl IntoSystemConfig<M>) -> App {
+    let mut settings = CrosstermWindowSettings::default();
+    settings.set_headless(width, height);
+
+    let mut app = App::new();
+    app.insert_resource(settings)
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(CrosstermPlugin)
+        .add_startup_system(startup_system);
+    app.update();
+    app
+}
+
+fn sprite(
+    sprites: &mut Assets<Sprite>,
+    stylemaps: &mut Assets<StyleMap>,
+    graphic: &str,
+    position: Position,
+    stylemap: StyleMap,
+) -> SpriteBundle {
+    SpriteBundle {
+        sprite: sprites.add(Sprite::new(graphic)),
+        position,
+        stylemap: stylemaps.add(stylemap),
+        ..Default::default()
+    }
+}
+
+fn spawn_ring_over_box(
+    mut commands: Commands,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    let white = StyleMap::with_bg(Color::White);
+    commands.spawn(sprite(&mut sprites, &mut stylemaps, "     \n     \n     ", Position::new(2, 1, 0), white));
+    commands.spawn(sprite(&mut sprites, &mut stylemaps, "@", Position::new(3, 2, 1), StyleMap::default()));
+    commands.spawn((
+        SpriteBundle {
+            visible: Visible::transparent(),
+            ..sprite(&mut sprites, &mut stylemaps, "#####\n#   #\n#####", Position::new(2, 1, 2), StyleMap::default())
+        },
+        Tag,
+    ));
+}
+
+fn spawn_walker_and_box(
+    mut commands: Commands,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    let white = StyleMap::with_bg(Color::White);
+    commands.spawn(sprite(&mut sprites, &mut stylemaps, "       \n       \n       ", Position::new(6, 1, 0), white));
+    commands.spawn((sprite(&mut sprites, &mut stylemaps, "@", Position::new(4, 2, 1), StyleMap::default()), Tag));
+    commands.spawn(sprite(&mut sprites, &mut stylemaps, "#", Position::new(9, 1, 1), StyleMap::default()));
+}
+
+fn move_tagged(app: &mut App, dx: i32) {
+    for mut pos in app.world.query_filtered::<&mut Position, With<Tag>>().iter_mut(&mut app.world) {
+        pos.x += dx;
+    }
+    app.update();
+}
+
+#[test]
+fn transparent_cells_show_what_is_underneath() {
+    let mut app = headless_app(20, 5, spawn_ring_over_box);
+    let screen = app.world.resource::<HeadlessBackend>().clone();
+
+    // The "@" sits on top of the box, and the ring's hole lets both of them show through
+    assert_eq!(screen.line(1).unwrap(), "  #####             ");
+    assert_eq!(screen.line(2).unwrap(), "  #@  #             ");
+    assert_eq!(screen.line(3).unwrap(), "  #####             ");
+    assert_eq!(screen.cell(4, 2).unwrap().background, Color::White);
+    assert_eq!(screen.cell(3, 2).unwrap().background, Color::Reset);
+
+    // Moving the ring off of the box means the box has to be repaired where the ring used to be
+    move_tagged(&mut app, 10);
+    assert_eq!(screen.line(1).unwrap(), "            #####   ");
+    assert_eq!(screen.line(2).unwrap(), "   @        #   #   ");
+    assert_eq!(screen.cell(2, 2).unwrap().background, Color::White);
+    assert_eq!(screen.cell(3, 2).unwrap().background, Color::Reset);
+}
+
+#[test]
+fn cells_are_redrawn_after_a_sprite_moves_off_of_them() {
+    let mut app = headless_app(20, 5, spawn_walker_and_box);
+    let screen = app.world.resource::<HeadlessBackend>().clone();
+    assert_eq!(screen.line(2).unwrap(), "    @               ");
+
+    // Walk the "@" across the box. Wherever it's been the box shows up again, and the "#" is never drawn over
+    for x in 5..15 {
+        move_tagged(&mut app, 1);
+        assert_eq!(screen.cell(x, 2).unwrap().grapheme, "@");
+        for column in (6..13).filter(|column| *column != x) {
+            let background = screen.cell(column, 2).unwrap().background;
+            assert_eq!(background, Color::White, "column {} with the @ at {}", column, x);
+        }
+        assert_eq!(screen.line(1).unwrap(), "         #          ");
+    }
+
+    // Once the "@" is past it the box is back the way it started
+    assert_eq!(screen.line(2).unwrap(), "              @     ");
+    assert_eq!(screen.cell(4, 2).unwrap().background, Color::Reset);
+    assert!((6..13).all(|column| screen.cell(column, 2).unwrap().background == Color::White));
+}
+
+fn spawn_without_global_position(
+    mut commands: Commands,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    let bundle = sprite(&mut sprites, &mut stylemaps, "@", Position::new(3, 1, 0), StyleMap::default());
+    commands.spawn((bundle.position, bundle.sprite, bundle.stylemap, bundle.visible));
+}
+
+#[test]
+fn entities_without_a_global_position_are_still_drawn() {
+    let app = headless_app(20, 5, spawn_without_global_position);
+    let screen = app.world.resource::<HeadlessBackend>();
+    assert_eq!(screen.line(1).unwrap(), "   @                ");
+}
+
+#[derive(Component)]
+struct Button;
+
+fn spawn_button_under_dialog(
+    mut commands: Commands,
+    mut sprites: ResMut<Assets<Sprite>>,
+    mut stylemaps: ResMut<Assets<StyleMap>>,
+) {
+    let button = sprite(&mut sprites, &mut stylemaps, "[ OK ]", Position::new(0, 1, 0), StyleMap::default());
+    commands.spawn((button, Pickable, Button));
+    commands.spawn(sprite(&mut sprites, &mut stylemaps, "###", Position::new(0, 1, 1), StyleMap::default()));
+}
+
+fn click(app: &mut App, column: u16, row: u16) -> Vec<PickingEvent> {
+    use crossterm::event::{KeyModifiers, MouseEvent, MouseEventKind};
+
+    for kind in [MouseEventKind::Down(MouseButton::Left), MouseEventKind::Up(MouseButton::Left)] {
+        app.world.send_event(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE });
+    }
+    app.update();
+
+    let events = app.world.resource::<Events<PickingEvent>>();
+    events.get_reader().iter(events).copied().collect()
+}
+
+#[test]
+fn sprites_on_top_block_picking_even_if_they_are_not_pickable() {
+    let mut app = headless_app(20, 5, spawn_button_under_dialog);
+    let button = app.world.query_filtered::<Entity, With<Button>>().single(&app.world);
+
+    // The button is covered up where the "###" is drawn over it
+    assert_eq!(click(&mut app, 1, 1), vec![]);
+
+    let clicked = click(&mut app, 4, 1);
+    let left_click = PickingKind::Clicked(MouseButton::Left);
+    assert!(clicked.iter().any(|event| event.entity == button && event.kind == left_click));
+}
+
+#[test]
+fn only_changed_cells_are_written() {
+    let mut app = headless_app(20, 5, spawn_walker_and_box);
+    let screen = app.world.resource::<HeadlessBackend>().clone();
+    screen.take_written();
+
+    // Nothing changed, so nothing is sent to the terminal
+    app.update();
+    assert_eq!(screen.take_written(), vec![]);
+
+    // Moving the "@" one column only touches the cell it left and the cell it moved to
+    move_tagged(&mut app, 1);
+    let mut written = screen.take_written();
+    written.sort();
+    written.dedup();
+    assert_eq!(written, vec![(4, 2), (5, 2)]);
+    assert_eq!(screen.line(2).unwrap(), "     @              ");
+}